@@ -0,0 +1,69 @@
+//! Compare `LazyFormatExt::to_string_sized` with `ToString::to_string`, for
+//! outputs of a few different sizes. Run with `cargo bench`.
+//!
+//! This doesn't use a benchmarking framework, to avoid adding a dependency;
+//! each case is timed over enough iterations to smooth out the noise.
+
+use std::fmt::Display;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use lazy_format::prelude::*;
+
+const TARGET: Duration = Duration::from_millis(500);
+
+fn time_per_iter<T>(mut run: impl FnMut() -> T) -> Duration {
+    // Warm up, and find out how many iterations fit in the target time
+    let mut iters: u32 = 1;
+
+    loop {
+        let start = Instant::now();
+        for _ in 0..iters {
+            black_box(run());
+        }
+        let elapsed = start.elapsed();
+
+        if elapsed >= TARGET / 10 {
+            let iters = iters * 10;
+            let start = Instant::now();
+            for _ in 0..iters {
+                black_box(run());
+            }
+            return start.elapsed() / iters;
+        }
+
+        iters *= 2;
+    }
+}
+
+fn compare(name: &str, value: impl Display) {
+    let to_string = time_per_iter(|| value.to_string());
+    let to_string_sized = time_per_iter(|| value.to_string_sized().unwrap());
+
+    println!(
+        "{name:<28} to_string: {to_string:>10.2?}   to_string_sized: {to_string_sized:>10.2?}"
+    );
+}
+
+fn main() {
+    let word = "lazy";
+    compare("short (4 bytes)", lazy_format!("{word}"));
+
+    let numbers: Vec<u32> = (0..100).collect();
+    compare(
+        "100 numbers (~290 bytes)",
+        lazy_format!("{n}" sep ", " for n in &numbers),
+    );
+
+    let numbers: Vec<u32> = (0..10_000).collect();
+    compare(
+        "10k numbers (~49 KB)",
+        lazy_format!("{n}" sep ", " for n in &numbers),
+    );
+
+    let chunk = "x".repeat(1000);
+    compare(
+        "1k chunks (1 MB)",
+        lazy_format!("{chunk}" for _ in 0..1000),
+    );
+}
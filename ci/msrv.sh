@@ -0,0 +1,39 @@
+#!/usr/bin/env bash
+
+# Check lazy_format on its minimum supported Rust version (the `rust-version`
+# in Cargo.toml). The library is checked with every combination of features,
+# and the unit and doc tests are run, since they're what expand the macros.
+# The integration tests use syntax that needs a newer Rust, so they're only
+# run on stable.
+
+set -euo pipefail
+
+# The latest versions of some dev-dependencies need a newer Rust, and the
+# cargo of the minimum supported Rust version doesn't know how to avoid
+# them, so pin them to the last compatible versions.
+cargo generate-lockfile
+cargo update -p serde_json --precise 1.0.120
+cargo update -p serde --precise 1.0.210
+cargo update -p syn --precise 2.0.114
+cargo update -p ryu --precise 1.0.18
+cargo update -p unicode-ident --precise 1.0.22
+cargo update -p quote --precise 1.0.44
+cargo update -p proc-macro2 --precise 1.0.106
+
+features=(alloc std no-inline unicode-width heapless serde)
+
+for ((mask = 0; mask < 1 << ${#features[@]}; mask++)); do
+    selected=()
+
+    for i in "${!features[@]}"; do
+        if ((mask & 1 << i)); then
+            selected+=("${features[i]}")
+        fi
+    done
+
+    echo "Checking with features: ${selected[*]:-(none)}"
+    cargo check --no-default-features --features "${selected[*]:-}"
+done
+
+cargo test --lib --all-features
+cargo test --doc --all-features
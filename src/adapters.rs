@@ -0,0 +1,583 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Internal [`fmt::Write`] adapters, which sit between a lazy format and its
+final destination and transform or redirect the output as it's written.
+*/
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+/// Writer that forwards everything written to it to each of several
+/// destinations, in order, stopping at the first error.
+pub(crate) struct Tee<'a, 'b> {
+    pub dests: &'a mut [&'b mut dyn fmt::Write],
+}
+
+impl fmt::Write for Tee<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.dests.iter_mut().try_for_each(|dest| dest.write_str(s))
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.dests.iter_mut().try_for_each(|dest| dest.write_char(c))
+    }
+}
+
+/// Writer that discards everything written to it, and just counts the bytes.
+#[cfg(feature = "alloc")]
+pub(crate) struct ByteCounter {
+    pub len: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Write for ByteCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+/// Writer that discards everything written to it, and just counts the chars.
+pub(crate) struct CharCounter {
+    pub len: usize,
+}
+
+impl fmt::Write for CharCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.len += s.chars().count();
+        Ok(())
+    }
+
+    fn write_char(&mut self, _c: char) -> fmt::Result {
+        self.len += 1;
+        Ok(())
+    }
+}
+
+/// Writer that forwards everything written to it to `dest`, counting the
+/// chars as it goes.
+pub(crate) struct CharTally<W> {
+    pub dest: W,
+    pub len: usize,
+}
+
+impl<W: fmt::Write> fmt::Write for CharTally<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.len += s.chars().count();
+        self.dest.write_str(s)
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.len += 1;
+        self.dest.write_char(c)
+    }
+}
+
+/// Writer that checks that everything written through it matches the start
+/// of `remaining`, consuming it as it goes. Returns an error at the first
+/// mismatch, to stop the formatting early.
+pub(crate) struct Matcher<'a> {
+    pub remaining: &'a str,
+}
+
+impl fmt::Write for Matcher<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.remaining.strip_prefix(s) {
+            Some(rest) => {
+                self.remaining = rest;
+                Ok(())
+            }
+            None => Err(fmt::Error),
+        }
+    }
+}
+
+/// Writer that fills a byte slice. If something doesn't fit, as much of it
+/// as fits is written, up to a char boundary, and then `full` is set and an
+/// error is returned, so the slice always contains valid UTF-8.
+pub(crate) struct SliceWriter<'a> {
+    pub buffer: &'a mut [u8],
+    pub len: usize,
+    pub full: bool,
+}
+
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let available = self.buffer.len() - self.len;
+
+        let fits = if s.len() <= available {
+            s.len()
+        } else {
+            self.full = true;
+            (0..=available)
+                .rev()
+                .find(|&idx| s.is_char_boundary(idx))
+                .unwrap_or(0)
+        };
+
+        self.buffer[self.len..self.len + fits].copy_from_slice(&s.as_bytes()[..fits]);
+        self.len += fits;
+
+        if self.full {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Writer that appends to a string, up to `max` bytes in total. Like
+/// [`SliceWriter`], if something doesn't fit, as much of it as fits is
+/// written, up to a char boundary, and then `full` is set and an error is
+/// returned.
+#[cfg(feature = "alloc")]
+pub(crate) struct StringLimiter {
+    pub string: String,
+    pub max: usize,
+    pub full: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Write for StringLimiter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let available = self.max - self.string.len();
+
+        if s.len() <= available {
+            self.string.push_str(s);
+            return Ok(());
+        }
+
+        let fits = (0..=available)
+            .rev()
+            .find(|&idx| s.is_char_boundary(idx))
+            .unwrap_or(0);
+
+        self.string.push_str(&s[..fits]);
+        self.full = true;
+        Err(fmt::Error)
+    }
+}
+
+/// Writer that appends to a [`heapless::String`]. If something doesn't fit,
+/// `full` is set and an error is returned, to stop the formatting early.
+#[cfg(feature = "heapless")]
+pub(crate) struct HeaplessWriter<const N: usize> {
+    pub string: heapless::String<N>,
+    pub full: bool,
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> fmt::Write for HeaplessWriter<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.string.push_str(s).map_err(|()| {
+            self.full = true;
+            fmt::Error
+        })
+    }
+}
+
+/// Write `count` spaces to `dest`.
+pub(crate) fn write_spaces(dest: &mut impl fmt::Write, mut count: usize) -> fmt::Result {
+    const SPACES: &str = "                                ";
+
+    while count > 0 {
+        let chunk = count.min(SPACES.len());
+        dest.write_str(&SPACES[..chunk])?;
+        count -= chunk;
+    }
+
+    Ok(())
+}
+
+/// Writer that indents each non-empty line written through it. Tracks
+/// whether it's at the start of a line across calls to `write_str`, so that
+/// lines written in several pieces are only indented once.
+pub(crate) struct Indenter<W> {
+    pub dest: W,
+    pub indent: usize,
+    pub at_line_start: bool,
+}
+
+impl<W: fmt::Write> fmt::Write for Indenter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.split_inclusive('\n').try_for_each(|line| {
+            if self.at_line_start && line != "\n" {
+                write_spaces(&mut self.dest, self.indent)?;
+            }
+
+            self.at_line_start = line.ends_with('\n');
+            self.dest.write_str(line)
+        })
+    }
+}
+
+/// Writer that writes `prefix` at the start of each line written through
+/// it. Like [`Indenter`], it tracks whether it's at the start of a line
+/// across calls to `write_str`. The prefix is only written once something
+/// is written on the line, so a trailing newline isn't followed by a prefix.
+pub(crate) struct LinePrefixer<'a, W> {
+    pub dest: W,
+    pub prefix: &'a str,
+    pub at_line_start: bool,
+}
+
+impl<W: fmt::Write> fmt::Write for LinePrefixer<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.split_inclusive('\n').try_for_each(|line| {
+            if self.at_line_start {
+                self.dest.write_str(self.prefix)?;
+            }
+
+            self.at_line_start = line.ends_with('\n');
+            self.dest.write_str(line)
+        })
+    }
+}
+
+/// Writer that replaces each line break (`\n` or `\r\n`) written through it
+/// with `separator`. A `\r` at the end of a chunk is held in `pending_cr`
+/// until the next chunk shows whether it's part of a `\r\n`; call `finish`
+/// to write it once formatting has succeeded.
+pub(crate) struct NewlineReplacer<'a, W> {
+    pub dest: W,
+    pub separator: &'a str,
+    pub pending_cr: bool,
+}
+
+impl<W: fmt::Write> NewlineReplacer<'_, W> {
+    pub fn finish(mut self) -> fmt::Result {
+        if self.pending_cr {
+            self.dest.write_char('\r')?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for NewlineReplacer<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.is_empty() {
+            return Ok(());
+        }
+
+        let mut rest = s;
+
+        if core::mem::take(&mut self.pending_cr) {
+            match rest.strip_prefix('\n') {
+                Some(after) => {
+                    self.dest.write_str(self.separator)?;
+                    rest = after;
+                }
+                None => self.dest.write_char('\r')?,
+            }
+        }
+
+        while let Some(idx) = rest.find('\n') {
+            let line = &rest[..idx];
+            self.dest.write_str(line.strip_suffix('\r').unwrap_or(line))?;
+            self.dest.write_str(self.separator)?;
+            rest = &rest[idx + 1..];
+        }
+
+        match rest.strip_suffix('\r') {
+            Some(line) => {
+                self.pending_cr = true;
+                self.dest.write_str(line)
+            }
+            None => self.dest.write_str(rest),
+        }
+    }
+}
+
+/// Writer that escapes the HTML special characters `&`, `<`, `>`, `"`, and
+/// `'` in everything written through it. Each of these is a single byte, so
+/// every chunk can be escaped independently.
+pub(crate) struct HtmlEscaper<W> {
+    pub dest: W,
+}
+
+impl<W: fmt::Write> fmt::Write for HtmlEscaper<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+
+        while let Some(idx) = rest.find(['&', '<', '>', '"', '\'']) {
+            let (plain, special) = rest.split_at(idx);
+            self.dest.write_str(plain)?;
+            self.dest.write_str(match special.as_bytes()[0] {
+                b'&' => "&amp;",
+                b'<' => "&lt;",
+                b'>' => "&gt;",
+                b'"' => "&quot;",
+                _ => "&#39;",
+            })?;
+            rest = &special[1..];
+        }
+
+        self.dest.write_str(rest)
+    }
+}
+
+/// Writer that converts ASCII letters in everything written through it to
+/// upper or lower case. Other chars are left alone, so the conversion never
+/// changes the length of the output, and every chunk can be converted
+/// independently, in pieces that fit in a small buffer.
+pub(crate) struct AsciiCaseConverter<W> {
+    pub dest: W,
+    pub upper: bool,
+}
+
+impl<W: fmt::Write> fmt::Write for AsciiCaseConverter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut buffer = [0u8; 64];
+        let mut rest = s;
+
+        while !rest.is_empty() {
+            let mut len = rest.len().min(buffer.len());
+            while !rest.is_char_boundary(len) {
+                len -= 1;
+            }
+
+            let (chunk, tail) = rest.split_at(len);
+            let converted = &mut buffer[..len];
+            converted.copy_from_slice(chunk.as_bytes());
+
+            if self.upper {
+                converted.make_ascii_uppercase();
+            } else {
+                converted.make_ascii_lowercase();
+            }
+
+            self.dest
+                .write_str(core::str::from_utf8(converted).map_err(|_| fmt::Error)?)?;
+            rest = tail;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writer that escapes everything written through it as the contents of a
+/// JSON string. Like with [`HtmlEscaper`], everything that needs escaping is
+/// a single byte, so every chunk can be escaped independently.
+pub(crate) struct JsonEscaper<W> {
+    pub dest: W,
+}
+
+impl<W: fmt::Write> fmt::Write for JsonEscaper<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+
+        let mut rest = s;
+
+        while let Some(idx) = rest.find(|c| matches!(c, '"' | '\\' | '\0'..='\x1f')) {
+            let (plain, special) = rest.split_at(idx);
+            self.dest.write_str(plain)?;
+
+            let byte = special.as_bytes()[0];
+            match byte {
+                b'"' => self.dest.write_str("\\\"")?,
+                b'\\' => self.dest.write_str("\\\\")?,
+                b'\n' => self.dest.write_str("\\n")?,
+                b'\r' => self.dest.write_str("\\r")?,
+                b'\t' => self.dest.write_str("\\t")?,
+                0x08 => self.dest.write_str("\\b")?,
+                0x0C => self.dest.write_str("\\f")?,
+                _ => {
+                    self.dest.write_str("\\u00")?;
+                    self.dest.write_char(HEX[usize::from(byte >> 4)] as char)?;
+                    self.dest.write_char(HEX[usize::from(byte & 0xF)] as char)?;
+                }
+            }
+
+            rest = &special[1..];
+        }
+
+        self.dest.write_str(rest)
+    }
+}
+
+/// Writer that checks whether anything written through it would need to be
+/// quoted in a CSV field: a comma, a double quote, or a line break. Returns
+/// an error once it finds one, to stop the formatting early.
+pub(crate) struct CsvScanner {
+    pub needs_quotes: bool,
+}
+
+impl fmt::Write for CsvScanner {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.contains([',', '"', '\n', '\r']) {
+            self.needs_quotes = true;
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Writer that drops the first `skip` bytes written through it, and forwards
+/// the rest to `dest`. `skip` has to be on a char boundary of the output.
+pub(crate) struct Skipper<W> {
+    pub dest: W,
+    pub skip: usize,
+}
+
+impl<W: fmt::Write> fmt::Write for Skipper<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.skip >= s.len() {
+            self.skip -= s.len();
+            return Ok(());
+        }
+
+        let rest = s.get(self.skip..).ok_or(fmt::Error)?;
+        self.skip = 0;
+        self.dest.write_str(rest)
+    }
+}
+
+/// Writer that doubles every double quote written through it, as in a quoted
+/// CSV field.
+pub(crate) struct QuoteDoubler<W> {
+    pub dest: W,
+}
+
+impl<W: fmt::Write> fmt::Write for QuoteDoubler<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+
+        while let Some(idx) = rest.find('"') {
+            let (plain, quoted) = rest.split_at(idx + 1);
+            self.dest.write_str(plain)?;
+            self.dest.write_char('"')?;
+            rest = quoted;
+        }
+
+        self.dest.write_str(rest)
+    }
+}
+
+/// Writer that forwards at most `max_cols` columns to `dest`, as measured by
+/// `width`, including the ellipsis that's written if there was any more
+/// output. Chars that only fit if there's no more output are held in
+/// `pending` until that's known; call `finish` to write them once formatting
+/// has succeeded. If they don't fit in `pending`, they're only counted, and
+/// `overflowed` is set; the caller has to format the value again, and write
+/// everything after the first `written` bytes, with a `Skipper`. Once it's
+/// truncated, it returns an error, to stop the formatting early; use
+/// `truncated` to distinguish this from a real error.
+pub(crate) struct Truncator<'a, W> {
+    pub dest: W,
+    pub width: fn(char) -> usize,
+    pub cols: usize,
+    pub max_cols: usize,
+    pub ellipsis: &'a str,
+    pub ellipsis_cols: usize,
+    pub written: usize,
+    pub pending: [u8; 64],
+    pub pending_len: usize,
+    pub pending_cols: usize,
+    pub overflowed: bool,
+    pub truncated: bool,
+}
+
+impl<'a, W: fmt::Write> Truncator<'a, W> {
+    pub fn new(dest: W, max_cols: usize, ellipsis: &'a str, width: fn(char) -> usize) -> Self {
+        Self {
+            dest,
+            width,
+            cols: 0,
+            max_cols,
+            ellipsis,
+            ellipsis_cols: ellipsis.chars().map(width).sum(),
+            written: 0,
+            pending: [0; 64],
+            pending_len: 0,
+            pending_cols: 0,
+            overflowed: false,
+            truncated: false,
+        }
+    }
+
+    pub fn finish(&mut self) -> fmt::Result {
+        let pending = core::str::from_utf8(&self.pending[..self.pending_len]).map_err(|_| fmt::Error)?;
+        self.dest.write_str(pending)
+    }
+
+    // If the ellipsis is wider than the limit by itself, only as much of it
+    // as fits is written
+    fn truncate(&mut self) -> fmt::Result {
+        self.truncated = true;
+
+        let mut cols = self.cols;
+        let mut split = self.ellipsis.len();
+
+        for (idx, c) in self.ellipsis.char_indices() {
+            cols += (self.width)(c);
+            if cols > self.max_cols {
+                split = idx;
+                break;
+            }
+        }
+
+        self.dest.write_str(&self.ellipsis[..split])?;
+        Err(fmt::Error)
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for Truncator<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.truncated {
+            return Err(fmt::Error);
+        }
+
+        // Write everything that fits even with the ellipsis directly
+        let content_cols = self.max_cols.saturating_sub(self.ellipsis_cols);
+        let mut split = 0;
+
+        if self.pending_len == 0 {
+            split = s.len();
+
+            for (idx, c) in s.char_indices() {
+                let width = (self.width)(c);
+                if self.cols + width > content_cols {
+                    split = idx;
+                    break;
+                }
+                self.cols += width;
+            }
+        }
+
+        let (direct, rest) = s.split_at(split);
+        self.dest.write_str(direct)?;
+        self.written += direct.len();
+
+        // Hold on to the rest, as long as it fits without the ellipsis. Once
+        // the pending buffer fills up, only count the columns of the rest.
+        for c in rest.chars() {
+            let width = (self.width)(c);
+
+            if self.cols + self.pending_cols + width > self.max_cols {
+                return self.truncate();
+            }
+
+            let end = self.pending_len + c.len_utf8();
+
+            if self.overflowed || end > self.pending.len() {
+                self.overflowed = true;
+            } else {
+                c.encode_utf8(&mut self.pending[self.pending_len..end]);
+                self.pending_len = end;
+            }
+
+            self.pending_cols += width;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use core::fmt::{self, Debug, Display, Formatter};
+
+use alloc::{boxed::Box, vec::Vec};
+
+enum Part<'a> {
+    Literal(&'static str),
+    Arg(Box<dyn Display + 'a>),
+}
+
+/**
+A builder for lazy formats whose structure is only known at runtime. Each
+literal string and [`Display`] argument added to the builder is stored, and
+[`build`][LazyBuilder::build] creates a [`BuiltFormat`], which writes them
+all, in order, each time it's formatted. The arguments are formatted as
+though with `{}`.
+
+[`lazy_format!`][crate::lazy_format] is still the best choice when the
+structure of the format is known ahead of time: it's checked at compile
+time, and doesn't allocate. The builder stores its parts in a [`Vec`], and
+its arguments in [`Box`]es. Requires the `alloc` feature.
+
+# Example
+
+```
+use lazy_format::{lazy_format, LazyBuilder};
+
+let fields = [("name", "Ferris"), ("species", "crab")];
+
+let mut builder = LazyBuilder::new().lit("{");
+for (i, (key, value)) in fields.iter().enumerate() {
+    if i > 0 {
+        builder = builder.lit(", ");
+    }
+    builder = builder.arg(key).lit(": ").arg(lazy_format!("{value:?}"));
+}
+let built = builder.lit("}").build();
+
+assert_eq!(built.to_string(), r#"{name: "Ferris", species: "crab"}"#);
+```
+
+[`Vec`]: https://doc.rust-lang.org/std/vec/struct.Vec.html
+[`Box`]: https://doc.rust-lang.org/std/boxed/struct.Box.html
+*/
+#[derive(Default)]
+pub struct LazyBuilder<'a> {
+    parts: Vec<Part<'a>>,
+}
+
+impl<'a> LazyBuilder<'a> {
+    /// Create a new, empty builder.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self { parts: Vec::new() }
+    }
+
+    /// Add a literal string, which is written as-is.
+    #[inline]
+    #[must_use]
+    pub fn lit(mut self, literal: &'static str) -> Self {
+        self.parts.push(Part::Literal(literal));
+        self
+    }
+
+    /// Add an argument, which is formatted with its [`Display`]
+    /// implementation each time the built format is formatted.
+    #[inline]
+    #[must_use]
+    pub fn arg(mut self, arg: impl Display + 'a) -> Self {
+        self.parts.push(Part::Arg(Box::new(arg)));
+        self
+    }
+
+    /// Finish building, and get the lazy format.
+    #[inline]
+    #[must_use]
+    pub fn build(self) -> BuiltFormat<'a> {
+        BuiltFormat { parts: self.parts }
+    }
+}
+
+impl Debug for LazyBuilder<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyBuilder")
+            .field("parts", &self.parts.len())
+            .finish()
+    }
+}
+
+/// A lazy format built by a [`LazyBuilder`].
+pub struct BuiltFormat<'a> {
+    parts: Vec<Part<'a>>,
+}
+
+impl Display for BuiltFormat<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.parts.iter().try_for_each(|part| match part {
+            Part::Literal(literal) => f.write_str(literal),
+            Part::Arg(arg) => fmt::Write::write_fmt(f, format_args!("{}", arg)),
+        })
+    }
+}
+
+impl Debug for BuiltFormat<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BuiltFormat")
+            .field("parts", &self.parts.len())
+            .finish()
+    }
+}
@@ -0,0 +1,71 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use core::cell::Cell;
+use core::fmt::{self, Display, Formatter};
+
+/**
+Wrap a lazy format to count how many times it's formatted. Because lazy
+formats re-evaluate their arguments every time they're formatted, this is
+useful in tests to catch values that are accidentally formatted more than
+once.
+
+# Example
+
+```
+use lazy_format::{count_renders, lazy_format};
+
+let value = 10;
+let counted = count_renders(lazy_format!("value: {value}"));
+assert_eq!(counted.render_count(), 0);
+
+let first = counted.to_string();
+let second = format!("{counted} / {counted}");
+
+assert_eq!(first, "value: 10");
+assert_eq!(second, "value: 10 / value: 10");
+assert_eq!(counted.render_count(), 3);
+```
+*/
+#[inline]
+pub fn count_renders<T: Display>(inner: T) -> CountRenders<T> {
+    CountRenders {
+        inner,
+        count: Cell::new(0),
+    }
+}
+
+/// Display wrapper that counts how many times it's been formatted. Created
+/// with [`count_renders`].
+#[derive(Debug, Clone)]
+pub struct CountRenders<T> {
+    inner: T,
+    count: Cell<usize>,
+}
+
+impl<T> CountRenders<T> {
+    /// The number of times this value has been formatted so far.
+    #[inline]
+    #[must_use]
+    pub fn render_count(&self) -> usize {
+        self.count.get()
+    }
+
+    /// Get the wrapped value.
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Display> Display for CountRenders<T> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.count.set(self.count.get() + 1);
+        self.inner.fmt(f)
+    }
+}
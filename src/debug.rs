@@ -0,0 +1,102 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use core::fmt::{self, Debug, Display, Formatter};
+
+/**
+Lazily format the items of a collection as a list, using each item's
+[`Debug`] implementation, the same way as [`Formatter::debug_list`]. The
+collection is cloned and iterated each time the value is formatted, so it
+should usually be a reference or a cheap iterator. Like the standard
+debug builders, the alternate flag (`{:#}`) selects pretty-printed,
+multi-line output.
+
+# Example
+
+```
+use lazy_format::{lazy_debug_list, lazy_format};
+
+let names = ["Alice", "Bob"];
+let list = lazy_debug_list(&names);
+
+assert_eq!(list.to_string(), r#"["Alice", "Bob"]"#);
+assert_eq!(format!("{list:#}"), "[\n    \"Alice\",\n    \"Bob\",\n]");
+assert_eq!(lazy_format!("names: {list}").to_string(), r#"names: ["Alice", "Bob"]"#);
+```
+*/
+#[inline]
+pub fn lazy_debug_list<I>(items: I) -> DebugList<I>
+where
+    I: IntoIterator + Clone,
+    I::Item: Debug,
+{
+    DebugList { items }
+}
+
+/// Display wrapper that formats a collection like [`Formatter::debug_list`].
+/// Created with [`lazy_debug_list`].
+#[derive(Debug, Clone, Copy)]
+pub struct DebugList<I> {
+    items: I,
+}
+
+impl<I> Display for DebugList<I>
+where
+    I: IntoIterator + Clone,
+    I::Item: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.items.clone()).finish()
+    }
+}
+
+/**
+Lazily format the key-value pairs of a collection as a map, using the
+[`Debug`] implementations of the keys and values, the same way as
+[`Formatter::debug_map`]. As with [`lazy_debug_list`], the collection is
+cloned and iterated each time the value is formatted, and the alternate
+flag selects pretty-printed output.
+
+# Example
+
+```
+use std::collections::BTreeMap;
+use lazy_format::lazy_debug_map;
+
+let scores = BTreeMap::from([("Alice", 10), ("Bob", 7)]);
+let map = lazy_debug_map(&scores);
+
+assert_eq!(map.to_string(), r#"{"Alice": 10, "Bob": 7}"#);
+assert_eq!(lazy_debug_map([(1, 'a')]).to_string(), "{1: 'a'}");
+```
+*/
+#[inline]
+pub fn lazy_debug_map<I, K, V>(entries: I) -> DebugMap<I>
+where
+    I: IntoIterator<Item = (K, V)> + Clone,
+    K: Debug,
+    V: Debug,
+{
+    DebugMap { entries }
+}
+
+/// Display wrapper that formats key-value pairs like
+/// [`Formatter::debug_map`]. Created with [`lazy_debug_map`].
+#[derive(Debug, Clone, Copy)]
+pub struct DebugMap<I> {
+    entries: I,
+}
+
+impl<I, K, V> Display for DebugMap<I>
+where
+    I: IntoIterator<Item = (K, V)> + Clone,
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.entries.clone()).finish()
+    }
+}
@@ -0,0 +1,479 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+#[cfg(feature = "alloc")]
+use core::cmp::Ordering;
+use core::fmt::{self, Display};
+
+#[cfg(feature = "alloc")]
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+};
+
+#[cfg(feature = "alloc")]
+use crate::adapters::{ByteCounter, StringLimiter};
+use crate::adapters::{CharCounter, Matcher, SliceWriter, Tee};
+#[cfg(feature = "heapless")]
+use crate::adapters::HeaplessWriter;
+
+/**
+Extension methods for lazy formats. These are implemented for every
+[`Display`] type, so they're available on the output of [`lazy_format!`]
+and [`make_lazy_format!`], as well as on any other displayable value.
+
+[`lazy_format!`]: crate::lazy_format
+[`make_lazy_format!`]: crate::make_lazy_format
+*/
+pub trait LazyFormatExt: Display {
+    /**
+    Format this value into several destinations at once. The value is only
+    formatted once; each piece of output is forwarded to every destination,
+    in order. Formatting stops at the first error from any destination.
+
+    # Example
+
+    ```
+    use lazy_format::prelude::*;
+
+    let mut log = String::new();
+    let mut out = String::new();
+
+    lazy_format!("{} + {} = {}", 1, 2, 3)
+        .write_tee(&mut [&mut log, &mut out])
+        .unwrap();
+
+    assert_eq!(log, "1 + 2 = 3");
+    assert_eq!(out, "1 + 2 = 3");
+    ```
+    */
+    fn write_tee(&self, dests: &mut [&mut dyn fmt::Write]) -> fmt::Result {
+        fmt::Write::write_fmt(&mut Tee { dests }, format_args!("{}", self))
+    }
+
+    /**
+    Check if the output of this value is exactly `expected`, without
+    allocating. The output is compared with `expected` as it's written, and
+    formatting stops at the first mismatch. If formatting fails for any other
+    reason, this returns `false`.
+
+    # Example
+
+    ```
+    use lazy_format::prelude::*;
+
+    let items = [1, 2, 3];
+    let list = lazy_format!("{item}" sep ", " for item in items);
+
+    assert!(list.eq_str("1, 2, 3"));
+    assert!(!list.eq_str("1, 2"));
+    assert!(!list.eq_str("1, 2, 3, 4"));
+    ```
+    */
+    fn eq_str(&self, expected: &str) -> bool {
+        let mut matcher = Matcher {
+            remaining: expected,
+        };
+
+        fmt::Write::write_fmt(&mut matcher, format_args!("{}", self)).is_ok()
+            && matcher.remaining.is_empty()
+    }
+
+    /**
+    Count the number of chars in the output of this value, without
+    allocating. This is the number of unicode scalar values, which is a
+    reasonable proxy for the display width of simple text, but differs from
+    it for wide characters (such as CJK text), combining characters, and
+    control characters.
+
+    # Example
+
+    ```
+    use lazy_format::prelude::*;
+
+    let name = "Zoë";
+    assert_eq!(lazy_format!("Hello, {name}!").char_len(), Ok(11));
+    ```
+    */
+    fn char_len(&self) -> Result<usize, fmt::Error> {
+        let mut counter = CharCounter { len: 0 };
+        fmt::Write::write_fmt(&mut counter, format_args!("{}", self))?;
+        Ok(counter.len)
+    }
+
+    /**
+    Render this value into a byte buffer, without allocating, and return the
+    number of bytes written. If the output doesn't fit, this returns
+    [`RenderError::Full`]; in that case, the buffer contains as much of the
+    output as fit, cut off at a char boundary, so it's never left with
+    partial UTF-8. If the [`Display`] implementation returns an error of its
+    own, this returns [`RenderError::Format`].
+
+    # Example
+
+    ```
+    use lazy_format::prelude::*;
+
+    let value = 42;
+    let mut buffer = [0u8; 16];
+
+    let len = lazy_format!("value: {value}").render_to_slice(&mut buffer).unwrap();
+    assert_eq!(&buffer[..len], b"value: 42");
+
+    let mut small = [0u8; 4];
+    assert!(lazy_format!("value: {value}").render_to_slice(&mut small).is_err());
+    ```
+    */
+    fn render_to_slice(&self, buffer: &mut [u8]) -> Result<usize, RenderError<BufferFull>> {
+        let mut dest = SliceWriter {
+            buffer,
+            len: 0,
+            full: false,
+        };
+
+        match fmt::Write::write_fmt(&mut dest, format_args!("{}", self)) {
+            Ok(()) => Ok(dest.len),
+            Err(fmt::Error) if dest.full => Err(RenderError::Full(BufferFull)),
+            Err(fmt::Error) => Err(RenderError::Format),
+        }
+    }
+
+    /**
+    Render this value into a [`heapless::String`] with a capacity of `N`
+    bytes, without allocating. This is the same as
+    [`render_to_slice`][LazyFormatExt::render_to_slice], but produces a
+    string that can be stored and passed around, for `no_std` code that
+    already uses [`heapless`]. If the output doesn't fit, this returns
+    [`RenderError::Full`] with a [`CapacityError`], and if the [`Display`]
+    implementation returns an error of its own, this returns
+    [`RenderError::Format`]. Requires the `heapless` feature.
+
+    # Example
+
+    ```
+    use lazy_format::prelude::*;
+
+    let value = 42;
+
+    let rendered = lazy_format!("value: {value}").render_to_heapless::<16>().unwrap();
+    assert_eq!(rendered, "value: 42");
+
+    assert!(lazy_format!("value: {value}").render_to_heapless::<4>().is_err());
+    ```
+
+    [`heapless`]: https://docs.rs/heapless
+    [`heapless::String`]: https://docs.rs/heapless/0.8/heapless/struct.String.html
+    */
+    #[cfg(feature = "heapless")]
+    fn render_to_heapless<const N: usize>(
+        &self,
+    ) -> Result<heapless::String<N>, RenderError<CapacityError>> {
+        let mut dest = HeaplessWriter {
+            string: heapless::String::new(),
+            full: false,
+        };
+
+        match fmt::Write::write_fmt(&mut dest, format_args!("{}", self)) {
+            Ok(()) => Ok(dest.string),
+            Err(fmt::Error) if dest.full => Err(RenderError::Full(CapacityError)),
+            Err(fmt::Error) => Err(RenderError::Format),
+        }
+    }
+
+    /**
+    Get the output of this value as a [`Cow`]. In general this renders the
+    value into a [`Cow::Owned`] string, but a [`LazyStr`][crate::LazyStr]
+    (created by a [`lazy_format!`] without any placeholders) has its own
+    `as_cow` method, which returns a [`Cow::Borrowed`] without allocating.
+    Note that this only works when the concrete type is known; an opaque
+    `impl Display` always uses this method.
+
+    # Example
+
+    ```
+    use std::borrow::Cow;
+    use lazy_format::prelude::*;
+
+    let name = "World";
+    let greeting = lazy_format!("Hello, {name}!");
+    assert!(matches!(greeting.as_cow(), Cow::Owned(s) if s == "Hello, World!"));
+    ```
+
+    [`Cow`]: https://doc.rust-lang.org/std/borrow/enum.Cow.html
+    [`Cow::Borrowed`]: https://doc.rust-lang.org/std/borrow/enum.Cow.html#variant.Borrowed
+    [`Cow::Owned`]: https://doc.rust-lang.org/std/borrow/enum.Cow.html#variant.Owned
+    */
+    #[cfg(feature = "alloc")]
+    fn as_cow(&self) -> Cow<'static, str> {
+        Cow::Owned(self.to_string())
+    }
+
+    /**
+    Render this value to a [`String`] with exactly the right capacity
+    allocated up front. The value is formatted twice: once to count the
+    length of its output, and once more into the new string, so it's only
+    correct if the output is the same every time. The second pass usually
+    costs more than the reallocations it saves: the `to_string_sized`
+    benchmark finds it slower than [`to_string`] at every size it tries. Use
+    it when the exact capacity matters, like for a long-lived string that
+    shouldn't hold on to extra memory, rather than for speed.
+
+    # Errors
+
+    Unlike [`to_string`], which panics, this returns the error if the
+    [`Display`] implementation returns one during either pass.
+
+    # Example
+
+    ```
+    use lazy_format::prelude::*;
+
+    let items = [1, 2, 3];
+    let list = lazy_format!("{item}" sep ", " for item in items);
+    let rendered = list.to_string_sized().unwrap();
+
+    assert_eq!(rendered, "1, 2, 3");
+    assert_eq!(rendered.capacity(), 7);
+    ```
+
+    [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+    [`to_string`]: https://doc.rust-lang.org/std/string/trait.ToString.html#tymethod.to_string
+    */
+    #[cfg(feature = "alloc")]
+    fn to_string_sized(&self) -> Result<String, fmt::Error> {
+        let mut counter = ByteCounter { len: 0 };
+        fmt::Write::write_fmt(&mut counter, format_args!("{}", self))?;
+
+        let mut rendered = String::with_capacity(counter.len);
+        fmt::Write::write_fmt(&mut rendered, format_args!("{}", self))?;
+        Ok(rendered)
+    }
+
+    /**
+    Render this value to a [`String`] of at most `max` bytes. If the output
+    is longer than that, this returns [`RenderError::Full`] with a
+    [`TooLong`], which contains as much of the output as fit, cut off at a
+    char boundary; because a multi-byte char is never split, this may be
+    slightly shorter than `max`. Formatting stops as soon as the limit is
+    reached. If the [`Display`] implementation returns an error of its own,
+    this returns [`RenderError::Format`]. This is for fixed size fields
+    measured in bytes; see [`transform::truncate`] to limit the number of
+    chars instead.
+
+    # Example
+
+    ```
+    use lazy_format::prelude::*;
+    use lazy_format::RenderError;
+
+    let name = "Zoë";
+    let greeting = lazy_format!("Hi, {name}!");
+
+    assert_eq!(greeting.render_to_byte_limit(16).unwrap(), "Hi, Zoë!");
+
+    // "ë" is 2 bytes, so it doesn't fit in 7
+    match greeting.render_to_byte_limit(7) {
+        Err(RenderError::Full(err)) => assert_eq!(err.partial(), "Hi, Zo"),
+        _ => panic!("the greeting should have been cut off"),
+    }
+    ```
+
+    [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+    [`transform::truncate`]: crate::transform::truncate
+    */
+    #[cfg(feature = "alloc")]
+    fn render_to_byte_limit(&self, max: usize) -> Result<String, RenderError<TooLong>> {
+        let mut dest = StringLimiter {
+            string: String::new(),
+            max,
+            full: false,
+        };
+
+        match fmt::Write::write_fmt(&mut dest, format_args!("{}", self)) {
+            Ok(()) => Ok(dest.string),
+            Err(fmt::Error) if dest.full => Err(RenderError::Full(TooLong {
+                partial: dest.string,
+            })),
+            Err(fmt::Error) => Err(RenderError::Format),
+        }
+    }
+
+    /**
+    Compare the rendered output of this value with the rendered output of
+    `other`, lexically. This is how lazy formats implement [`Ord`] (and the
+    other comparison traits) when the `alloc` feature is enabled.
+
+    Note that this renders both values to a new [`String`] every time it's
+    called, so it allocates, and re-evaluates all the format arguments. When
+    sorting many lazy formats, consider [`sort_by_cached_key`] with
+    [`to_string`] instead, which renders each value only once.
+
+    A value that fails to render (by returning an error from its
+    [`Display`] implementation) is ordered before any value that renders
+    successfully, and equal to any other value that fails to render. This
+    never panics.
+
+    # Example
+
+    ```
+    use std::cmp::Ordering;
+    use lazy_format::prelude::*;
+
+    let a = 10;
+    let b = 9;
+
+    assert_eq!(lazy_format!("{a}").cmp_rendered(&lazy_format!("{b}")), Ordering::Less);
+    assert_eq!(lazy_format!("{b}").cmp_rendered(&b), Ordering::Equal);
+
+    let failing = make_lazy_format!(|_f| Err(std::fmt::Error));
+    assert_eq!(failing.cmp_rendered(&""), Ordering::Less);
+    ```
+
+    [`Ord`]: https://doc.rust-lang.org/std/cmp/trait.Ord.html
+    [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+    [`sort_by_cached_key`]: https://doc.rust-lang.org/std/primitive.slice.html#method.sort_by_cached_key
+    [`to_string`]: https://doc.rust-lang.org/std/string/trait.ToString.html#tymethod.to_string
+    */
+    #[cfg(feature = "alloc")]
+    fn cmp_rendered(&self, other: &(impl Display + ?Sized)) -> Ordering {
+        fn render(value: &(impl Display + ?Sized)) -> Option<String> {
+            let mut rendered = String::new();
+            fmt::Write::write_fmt(&mut rendered, format_args!("{}", value)).ok()?;
+            Some(rendered)
+        }
+
+        // `None` (a failed render) is less than any `Some`
+        render(self).cmp(&render(other))
+    }
+
+    /**
+    Write this value to an [`io::Write`], like a file or a socket, without
+    rendering it into a [`String`] first. This is a shorthand for
+    [`IoWriter::write_display`][crate::io::IoWriter::write_display]; the
+    [`io::Error`] that caused a failure is returned directly. Requires the
+    `std` feature.
+
+    # Example
+
+    ```
+    use lazy_format::prelude::*;
+
+    let items = [1, 2, 3];
+    let mut out: Vec<u8> = Vec::new();
+
+    lazy_format!("{item}\n" for item in items).render_to_writer(&mut out).unwrap();
+    assert_eq!(out, b"1\n2\n3\n");
+    ```
+
+    [`io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+    [`io::Error`]: https://doc.rust-lang.org/std/io/struct.Error.html
+    [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+    */
+    #[cfg(feature = "std")]
+    fn render_to_writer(&self, writer: &mut (impl std::io::Write + ?Sized)) -> std::io::Result<()> {
+        crate::io::IoWriter::new(writer).write_display(self)
+    }
+}
+
+impl<T: Display + ?Sized> LazyFormatExt for T {}
+
+/// The error returned by [`LazyFormatExt::render_to_slice`] when the output
+/// doesn't fit in the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferFull;
+
+impl Display for BufferFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the output doesn't fit in the buffer")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferFull {}
+
+/// The error returned by the `render_to_*` methods of [`LazyFormatExt`],
+/// like [`render_to_slice`][LazyFormatExt::render_to_slice]. `E` is the
+/// method's own error for output that doesn't fit, like [`BufferFull`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderError<E> {
+    /// The output doesn't fit in the destination.
+    Full(E),
+
+    /// The [`Display`] implementation returned an error of its own, like a
+    /// [`TryFormat`][crate::TryFormat] does when it fails.
+    Format,
+}
+
+impl<E: Display> Display for RenderError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::Full(err) => err.fmt(f),
+            RenderError::Format => f.write_str("the value returned an error while it was formatted"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for RenderError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RenderError::Full(err) => Some(err),
+            RenderError::Format => None,
+        }
+    }
+}
+
+/// The error returned by [`LazyFormatExt::render_to_heapless`] when the
+/// output doesn't fit in the string's capacity.
+#[cfg(feature = "heapless")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError;
+
+#[cfg(feature = "heapless")]
+impl Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the output doesn't fit in the string's capacity")
+    }
+}
+
+#[cfg(all(feature = "heapless", feature = "std"))]
+impl std::error::Error for CapacityError {}
+
+/// The error returned by [`LazyFormatExt::render_to_byte_limit`] when the
+/// output is longer than the limit. It contains as much of the output as fit.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooLong {
+    partial: String,
+}
+
+#[cfg(feature = "alloc")]
+impl TooLong {
+    /// The part of the output that fit within the limit.
+    #[inline]
+    #[must_use]
+    pub fn partial(&self) -> &str {
+        &self.partial
+    }
+
+    /// Get the part of the output that fit within the limit, as an owned
+    /// string.
+    #[inline]
+    #[must_use]
+    pub fn into_partial(self) -> String {
+        self.partial
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Display for TooLong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the output is longer than the byte limit")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TooLong {}
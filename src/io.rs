@@ -0,0 +1,124 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Writing lazy formats to [`io::Write`] destinations, like files and sockets,
+directly, without first formatting them into a [`String`]. Requires the
+`std` feature.
+
+[`String`]: std::string::String
+*/
+
+use core::fmt::{self, Display};
+
+use std::io;
+
+/**
+Adapter that implements [`fmt::Write`] for an [`io::Write`], by writing
+everything as UTF-8 with [`write_all`][io::Write::write_all]. Because
+[`fmt::Write`] can only report a [`fmt::Error`], the underlying
+[`io::Error`] is stored in the adapter, and can be retrieved afterwards
+with [`take_error`][IoWriter::take_error].
+
+Note that no buffering is performed, so each piece of output written by a
+lazy format turns into a separate `write_all`; consider wrapping unbuffered
+destinations in a [`BufWriter`][io::BufWriter].
+
+# Example
+
+```
+use std::fmt::Write;
+use lazy_format::lazy_format;
+use lazy_format::io::IoWriter;
+
+let mut out: Vec<u8> = Vec::new();
+let mut writer = IoWriter::new(&mut out);
+
+let name = "World";
+write!(writer, "{}", lazy_format!("Hello, {name}!")).unwrap();
+assert!(writer.take_error().is_none());
+
+assert_eq!(out, b"Hello, World!");
+```
+*/
+#[derive(Debug)]
+pub struct IoWriter<'a, W: ?Sized> {
+    writer: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: io::Write + ?Sized> IoWriter<'a, W> {
+    /// Create a new `IoWriter` that writes to `writer`.
+    #[inline]
+    #[must_use]
+    pub fn new(writer: &'a mut W) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    /**
+    Write `value` to the underlying writer. If formatting fails, the
+    [`io::Error`] that caused it is returned; if the failure came from the
+    value itself rather than from the writer, an [`io::Error`] of kind
+    [`Other`][io::ErrorKind::Other] is returned instead.
+    */
+    pub fn write_display(&mut self, value: impl Display) -> io::Result<()> {
+        match fmt::Write::write_fmt(self, format_args!("{}", value)) {
+            Ok(()) => Ok(()),
+            Err(fmt::Error) => Err(self
+                .take_error()
+                .unwrap_or_else(|| io::Error::new(io::ErrorKind::Other, "formatter error"))),
+        }
+    }
+
+    /// Take the [`io::Error`] from the most recent failed write, if any.
+    #[inline]
+    #[must_use]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+impl<W: io::Write + ?Sized> fmt::Write for IoWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.writer.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+/**
+Write `value` (usually a lazy format) to `writer`, without rendering it into
+a [`String`] first. If writing fails, the [`io::Error`] that caused it is
+returned, rather than an opaque [`fmt::Error`]. This is the same as
+[`IoWriter::write_display`] and
+[`LazyFormatExt::render_to_writer`][crate::LazyFormatExt::render_to_writer],
+as a free function.
+
+# Example
+
+```
+use lazy_format::{lazy_format, write_lazy};
+
+let mut out: Vec<u8> = Vec::new();
+let names = ["Alice", "Bob"];
+
+write_lazy(&mut out, &lazy_format!("{name}\n" for name in names)).unwrap();
+assert_eq!(out, b"Alice\nBob\n");
+```
+
+[`String`]: std::string::String
+*/
+#[inline]
+pub fn write_lazy<W: io::Write + ?Sized>(
+    writer: &mut W,
+    value: &(impl Display + ?Sized),
+) -> io::Result<()> {
+    IoWriter::new(writer).write_display(value)
+}
@@ -22,7 +22,8 @@ use std::fmt::Display;
 use lazy_format::prelude::*;
 
 // NOTE: This is profoundly insecure and you should never actually
-// render HTML without escape guards, code injection prevention, etc.
+// render HTML without escape guards, code injection prevention, etc. See
+// `lazy_format::transform::escape_html` for escaping untrusted content.
 fn html_tag<'a>(tag: &'a str, content: impl Display + 'a) -> impl Display + 'a {
     lazy_format!("<{tag}>{content}</{tag}>")
 }
@@ -31,51 +32,281 @@ let result = html_tag("div", html_tag("p", "Hello, World!")).to_string();
 assert_eq!(result, "<div><p>Hello, World!</p></div>");
 ```
 
+# Serialization
+
+With the `serde` feature, lazy formats (including [`LazyStr`]) implement
+`serde::Serialize`, and are serialized as strings. They're rendered with
+[`Serializer::collect_str`], which, for most serializers, writes the output
+directly without an intermediary [`String`]. This makes it possible to use
+lazy formats as fields of serializable structs:
+
+```
+use serde::Serialize;
+use lazy_format::lazy_format;
+
+#[derive(Serialize)]
+struct Event<M> {
+    id: u32,
+    message: M,
+}
+
+let (x, y) = (1, 2);
+let event = Event { id: 7, message: lazy_format!("moved to ({x}, {y})") };
+
+assert_eq!(
+    serde_json::to_string(&event).unwrap(),
+    r#"{"id":7,"message":"moved to (1, 2)"}"#,
+);
+```
+
+# Logging
+
+Lazy formats work well with logging libraries, since both `log` and `tracing`
+already defer formatting until a record is actually emitted. With `tracing`,
+use the `%` sigil to record a lazy format as a field; it's captured as a
+`Display` value, and only formatted if a subscriber records the field:
+
+```
+use lazy_format::{count_renders, lazy_format};
+
+let items = [1, 2, 3];
+let summary = count_renders(lazy_format!("{item}; " for item in items));
+
+// No subscriber is installed, so this event is disabled, and `summary` is
+// never formatted
+tracing::debug!(items = %summary, "processing");
+assert_eq!(summary.render_count(), 0);
+```
+
+There's no dedicated `tracing` integration in this crate. `tracing`'s
+`Value` trait is sealed, so it can't be implemented for lazy formats, and
+the `%` sigil (or [`tracing::field::display`]) already is the wrapper that
+records any `Display` value without formatting it up front.
+
+[`tracing::field::display`]: https://docs.rs/tracing/0.1/tracing/field/fn.display.html
+
+# Code size
+
+The [`Display`] implementation of each lazy format is marked `#[inline]`,
+which favors speed: the formatting code can be inlined into each place
+that the lazy format is written. In size-sensitive builds, like embedded
+firmware, that use many lazy formats, enable the `no-inline` feature to
+remove these hints, so that the compiler is free to keep each
+implementation out of line. The effect depends on the optimizer, so it's
+worth measuring for your own binary; no behavior changes either way.
+
 [`format!`]: https://doc.rust-lang.org/std/macro.format.html
 [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
 [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+[`Serializer::collect_str`]: https://docs.rs/serde/1/serde/trait.Serializer.html#method.collect_str
+*/
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use serde;
+
+/// The result of a `make_lazy_format!` closure, which can be either a
+/// [`fmt::Result`][core::fmt::Result], or `()` for a block whose writes can't
+/// fail. It's also implemented for `!`, so that closures that never return,
+/// like `|f| todo!()`, don't depend on the never type falling back to `()`.
+#[doc(hidden)]
+pub trait IntoFmtResult {
+    fn into_fmt_result(self) -> core::fmt::Result;
+}
+
+impl IntoFmtResult for core::fmt::Result {
+    #[inline(always)]
+    fn into_fmt_result(self) -> core::fmt::Result {
+        self
+    }
+}
+
+impl IntoFmtResult for () {
+    #[inline(always)]
+    fn into_fmt_result(self) -> core::fmt::Result {
+        Ok(())
+    }
+}
+
+/// Names the never type `!`, which can't be written directly outside of a
+/// function's return type on stable Rust.
+#[doc(hidden)]
+pub trait FnOutput {
+    type Output;
+}
+
+impl<T> FnOutput for fn() -> T {
+    type Output = T;
+}
+
+impl IntoFmtResult for <fn() -> ! as FnOutput>::Output {
+    #[inline(always)]
+    fn into_fmt_result(self) -> core::fmt::Result {
+        self
+    }
+}
+
+/// Reverse the iterator of a collection, for `rev` loops in `lazy_format!`.
+/// This is a function, rather than a call to [`Iterator::rev`] in the macro,
+/// so that the error for an iterator that isn't double-ended points at the
+/// collection.
+#[doc(hidden)]
+#[inline(always)]
+pub fn reversed<I>(collection: I) -> core::iter::Rev<I::IntoIter>
+where
+    I: IntoIterator,
+    I::IntoIter: DoubleEndedIterator,
+{
+    collection.into_iter().rev()
+}
+
+/// Pin down the argument type of the transform in `lazy_map!`, so that its
+/// parameter doesn't need a type annotation.
+#[doc(hidden)]
+#[inline(always)]
+pub fn typed_transform<T, R, F: Fn(&T) -> R>(_value: &T, transform: F) -> F {
+    transform
+}
+
+/// Collect the items for a `for ... in collect` loop in `lazy_format!`.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[inline]
+pub fn collect_vec<I: IntoIterator>(iter: I) -> alloc::vec::Vec<I::Item> {
+    iter.into_iter().collect()
+}
+
+/// Call `write` with `f`, truncating its output to the precision of `f`, if
+/// it has one. This is used by `lazy_format!("{}", value)`, so that it
+/// treats precision the same way as a string does.
+#[doc(hidden)]
+pub fn write_precise(
+    f: &mut core::fmt::Formatter<'_>,
+    write: impl Fn(&mut core::fmt::Formatter<'_>) -> core::fmt::Result,
+) -> core::fmt::Result {
+    struct WriteFn<F>(F);
+
+    impl<F: Fn(&mut core::fmt::Formatter<'_>) -> core::fmt::Result> core::fmt::Display
+        for WriteFn<F>
+    {
+        #[inline]
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            (self.0)(f)
+        }
+    }
+
+    match f.precision() {
+        None => write(f),
+        Some(precision) => core::fmt::Display::fmt(
+            &transform::truncate(WriteFn(write), precision, ""),
+            f,
+        ),
+    }
+}
+
+/**
+Compile-time analysis of a format string literal. Evaluates to a const
+`Option<&'static str>`, which is `Some` if the pattern doesn't contain any
+placeholders, in which case it contains the pattern with any `{{` or `}}`
+escapes unescaped.
 */
+#[macro_export]
+#[doc(hidden)]
+macro_rules! literal_str {
+    ($pattern:literal) => {{
+        // We want this to be const so that we can guarantee it's computed
+        // at compile time. Need to put the `LITERAL` global in a nested
+        // scope because otherwise it leaks out
+        const LITERAL: ::core::option::Option<&str> = {
+            const BUFFER: ::core::option::Option<([u8; $pattern.len()], usize)> = {
+                let bytes = $pattern.as_bytes();
+                let mut buffer = [0; $pattern.len()];
+                let mut i = 0;
+                let mut len = 0;
+
+                loop {
+                    if i >= bytes.len() {
+                        break ::core::option::Option::Some((buffer, len));
+                    }
+
+                    buffer[len] = bytes[i];
+                    len += 1;
+
+                    i += match bytes[i] {
+                        b'{' | b'}' if i + 1 < bytes.len() && bytes[i + 1] == bytes[i] => 2,
+                        b'{' | b'}' => break ::core::option::Option::None,
+                        _ => 1,
+                    };
+                }
+            };
+
+            const LEN: usize = match BUFFER {
+                ::core::option::Option::None => 0,
+                ::core::option::Option::Some((_, len)) => len,
+            };
+
+            // Copy the unescaped bytes into an array of exactly the right
+            // length, because slicing (like `split_at`) isn't const on our
+            // minimum supported Rust version.
+            const UNESCAPED: [u8; LEN] = {
+                let mut unescaped = [0; LEN];
+
+                if let ::core::option::Option::Some((buffer, _)) = BUFFER {
+                    let mut i = 0;
+
+                    while i < LEN {
+                        unescaped[i] = buffer[i];
+                        i += 1;
+                    }
+                }
+
+                unescaped
+            };
+
+            match BUFFER {
+                ::core::option::Option::None => ::core::option::Option::None,
+                ::core::option::Option::Some(_) => match ::core::str::from_utf8(&UNESCAPED) {
+                    ::core::result::Result::Ok(literal) => ::core::option::Option::Some(literal),
+                    ::core::result::Result::Err(_) => panic!("unescaped pattern is invalid utf8"),
+                },
+            }
+        };
+
+        LITERAL
+    }};
+}
 
 /**
 Smarter write macro. Encodes some common patterns, such as writing an empty
 string being a no-op. Used in the more complex lazy-format operations, like
 conditionals, where writing only strings or empty strings, is common.
+
+Patterns without any format arguments are classified at compile time:
+patterns without any placeholders (after unescaping any `{{` or `}}`) are
+written directly with `write_str`. Only patterns containing actual
+placeholders go through `format_args!`, which itself writes the literal
+segments between the placeholders with `write_str`.
 */
 #[macro_export]
 #[doc(hidden)]
 macro_rules! write {
     ($dest:expr, "" $(,)? ) => { ::core::fmt::Result::Ok(()) };
 
-    ($dest:expr, $pattern:literal $(,)? ) => {{
-        enum Style {
-            Empty,
-            Plain,
-            Format,
-        }
-
-        match {
-            // We want this to be const so that we can guarantee it's computed
-            // at compile time. Need to put the `STYLE` global in a nested
-            // scope because otherwise it leaks out
-            const STYLE: Style = match $pattern.as_bytes().split_first() {
-                ::core::option::Option::None => Style::Empty,
-                ::core::option::Option::Some((&(b'}' | b'{'), _)) => Style::Format,
-                ::core::option::Option::Some((_, mut s)) => loop {
-                    s = match s.split_first() {
-                        None => break Style::Plain,
-                        Some((&(b'}' | b'{'), _)) => break Style::Format,
-                        Some((_, s)) => s,
-                    };
-                }
-            };
-
-            STYLE
-        } {
-            Style::Empty => ::core::fmt::Result::Ok(()),
-            Style::Plain => ::core::fmt::Write::write_str($dest, $pattern),
-            Style::Format => ::core::fmt::Write::write_fmt($dest, ::core::format_args!($pattern)),
+    ($dest:expr, $pattern:literal $(,)? ) => {
+        match $crate::literal_str!($pattern) {
+            ::core::option::Option::Some("") => ::core::fmt::Result::Ok(()),
+            ::core::option::Option::Some(literal) => ::core::fmt::Write::write_str($dest, literal),
+            ::core::option::Option::None => {
+                ::core::fmt::Write::write_fmt($dest, ::core::format_args!($pattern))
+            }
         }
-    }};
+    };
 
     ($dest:expr, $pattern:literal, $($args:tt)+ ) => {
         ::core::fmt::Write::write_fmt($dest, ::core::format_args!($pattern, $($args)+))
@@ -87,12 +318,126 @@ Helper macro for common formatting shortcuts. In a few places in lazy_format,
 it's permitted to write either `lazy_format!(if cond => "foo")` or
 `lazy_format!(if cond => ("value: {}", value))`. This macro takes care of
 handling both cases.
+
+It can also be given a `$value`, which is inserted as the first positional
+argument, before any other arguments.
 */
 #[macro_export]
 #[doc(hidden)]
 macro_rules! write_tt {
+    // A parenthesized loop is written as a nested looping lazy format. This
+    // has to come first, since the format of the loop could be a literal.
+    ($dest:expr, ($output:tt $(wrap $prefix:tt $suffix:tt)? $(sep $separator:tt)? for $($loop:tt)+)) => {
+        ::core::fmt::Display::fmt(
+            &$crate::lazy_format!($output $(wrap $prefix $suffix)? $(sep $separator)? for $($loop)+),
+            $dest,
+        )
+    };
+
     ($dest:expr, $pattern:literal) => { $crate::write!($dest, $pattern) };
     ($dest:expr, ($pattern:literal $($args:tt)*)) => { $crate::write!($dest, $pattern $($args)*) };
+
+    ($dest:expr, $pattern:literal, $value:expr) => { $crate::write!($dest, $pattern, $value) };
+    ($dest:expr, ($pattern:literal $($args:tt)*), $value:expr) => {
+        $crate::write!($dest, $pattern, $value $($args)*)
+    };
+}
+
+/**
+Implement the comparison traits for a lazy format struct, by comparing the
+rendered output, if the `alloc` feature is enabled. See
+[`LazyFormatExt::cmp_rendered`]. `PartialEq` and `Eq` are only here because
+they're supertraits of `PartialOrd` and `Ord`.
+*/
+#[cfg(feature = "alloc")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! impl_rendered_cmp {
+    ($name:ident [$param:ident: $($bound:tt)*]) => {
+        impl<$param: $($bound)*> ::core::cmp::PartialEq for $name<$param> {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                $crate::LazyFormatExt::cmp_rendered(self, other).is_eq()
+            }
+        }
+
+        impl<$param: $($bound)*> ::core::cmp::Eq for $name<$param> {}
+
+        impl<$param: $($bound)*> ::core::cmp::PartialOrd for $name<$param> {
+            #[inline]
+            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                ::core::option::Option::Some(::core::cmp::Ord::cmp(self, other))
+            }
+        }
+
+        impl<$param: $($bound)*> ::core::cmp::Ord for $name<$param> {
+            #[inline]
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                $crate::LazyFormatExt::cmp_rendered(self, other)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "alloc"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! impl_rendered_cmp {
+    ($($tt:tt)*) => {};
+}
+
+/**
+Implement `serde::Serialize` for a lazy format struct, by serializing the
+rendered output as a string with [`Serializer::collect_str`], if the `serde`
+feature is enabled.
+
+[`Serializer::collect_str`]: https://docs.rs/serde/1/serde/trait.Serializer.html#method.collect_str
+*/
+#[cfg(feature = "serde")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! impl_serialize {
+    ($name:ident [$param:ident: $($bound:tt)*]) => {
+        impl<$param: $($bound)*> $crate::serde::Serialize for $name<$param> {
+            #[inline]
+            fn serialize<S: $crate::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> ::core::result::Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! impl_serialize {
+    ($($tt:tt)*) => {};
+}
+
+/**
+Emit the `fmt` method of a lazy format struct, marked `#[inline]` unless the
+`no-inline` feature is enabled.
+*/
+#[cfg(not(feature = "no-inline"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! fmt_inline {
+    ($($method:tt)*) => {
+        #[inline]
+        $($method)*
+    };
+}
+
+#[cfg(feature = "no-inline")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! fmt_inline {
+    ($($method:tt)*) => {
+        $($method)*
+    };
 }
 
 /// Test that an empty format string succeeds unconditionally.
@@ -137,9 +482,38 @@ fn test_write_string_pattern() {
     let x = 10;
 
     write_tt!(&mut WeirdDest, "Plain String").unwrap();
+    write_tt!(&mut WeirdDest, "{{Escaped}} String").unwrap();
     write_tt!(&mut WeirdDest, "Formatted String: {x}").unwrap_err();
 }
 
+/// Test that patterns containing only escaped braces are unescaped correctly
+#[test]
+fn test_write_escaped_pattern() {
+    use core::fmt;
+
+    struct Dest<'a>(&'a mut [u8], usize);
+
+    impl fmt::Write for Dest<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0[self.1..][..s.len()].copy_from_slice(s.as_bytes());
+            self.1 += s.len();
+            Ok(())
+        }
+
+        fn write_fmt(&mut self, _args: fmt::Arguments<'_>) -> fmt::Result {
+            Err(fmt::Error)
+        }
+    }
+
+    let mut buffer = [0; 32];
+    let mut dest = Dest(&mut buffer, 0);
+
+    write_tt!(&mut dest, "{{ a }} {{{{ }}b").unwrap();
+    write_tt!(&mut dest, "/é{{}}").unwrap();
+    let len = dest.1;
+    assert_eq!(&buffer[..len], "{ a } {{ }b/é{}".as_bytes());
+}
+
 /**
 Low level constructor for lazy format instances. Create a lazy formatter with a
 custom closure as its [`Display`] implementation, for complete control over
@@ -177,323 +551,2509 @@ let result = comma_separated.to_string();
 assert_eq!(result, "1, 2, 3, 4, 5");
 ```
 
-[`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
-[`Display::fmt`]: https://doc.rust-lang.org/core/fmt/trait.Display.html#tymethod.fmt
-*/
-#[macro_export]
-macro_rules! make_lazy_format {
-    (|$fmt:ident| $write:expr) => {{
-        #[derive(Clone, Copy)]
-        struct LazyFormat<F: Fn(&mut ::core::fmt::Formatter) -> ::core::fmt::Result>(F);
-
-        // TODO: customize Debug impl for semi_lazy_format to include value
-        impl<F: Fn(&mut ::core::fmt::Formatter) -> ::core::fmt::Result> ::core::fmt::Debug
-            for LazyFormat<F>
-        {
-            #[inline]
-            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-                f.write_str(concat!(
-                    "make_lazy_format!(",
-                    stringify!(|$fmt| $write),
-                    ")"
-                ))
-            }
-        }
-
-        impl<F: Fn(&mut ::core::fmt::Formatter) -> ::core::fmt::Result> ::core::fmt::Display
-            for LazyFormat<F>
-        {
-            #[inline]
-            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-                (self.0)(f)
-            }
-        }
+The closure can also be a block that doesn't return anything, which is
+convenient when it uses `?` for all of its writes, or writes in a loop; the
+macro returns `Ok(())` after it. A closure that returns an explicit
+[`fmt::Result`] works the same way as ever:
 
-        LazyFormat(move |$fmt: &mut ::core::fmt::Formatter| -> ::core::fmt::Result { $write })
-    }};
-}
+```
+use lazy_format::make_lazy_format;
 
-/**
-Lazily format something. Essentially the same as [`format!`], except that
-instead of formatting its arguments to a string, it captures them in an opaque
-struct, which can be formatted later. This allows you to build up formatting
-operations without any intermediary allocations or extra formatting calls. Also
-supports lazy conditional and looping constructs.
+let data = vec![1, 2, 3];
 
-The return value of this macro is left deliberately unspecified and
-undocumented. The most important this about it is its [`Display`]
-implementation, which executes the deferred formatting operation. It also
-provides a [`Debug`] implementation, which simply prints the
-[`lazy_format!`]`(...)` call without evaluating any of its arguments, as well
-as [`Clone`] and [`Copy`] if those traits are available in the captured
-context.
+let terminated = make_lazy_format!(|f| {
+    for value in &data {
+        write!(f, "{};", value)?;
+    }
+});
 
-Note that this macro is completely lazy; it captures the expressions to
-be formatted in the struct and doesn't evaluate them until the struct is
-actually written to a [`String`] or [`File`] or or other writable destination.
-This means that the argument expression will be evaluated *every* time the
-instance is written, which may not be what you want; be sure to eagerly perform
-any 1-time calculations you want to before calling `lazy_format!`.
+assert_eq!(terminated.to_string(), "1;2;3;");
+```
 
-# Basic example:
+The closure is always a `move` closure (writing `move |f|` explicitly is
+also allowed), so it takes ownership of everything it uses, and a lazy
+format that captures local variables can be returned from a function. To
+borrow from the environment instead, like a closure without `move`, write
+`ref |f|`:
 
 ```
 use std::fmt::Display;
-use lazy_format::lazy_format;
-
-fn get_hello() -> String {
-    String::from("Hello")
-}
+use lazy_format::make_lazy_format;
 
-fn get_world() -> String {
-    String::from("World")
+fn repeated(word: String, count: usize) -> impl Display {
+    make_lazy_format!(|f| (0..count).try_for_each(|_| f.write_str(&word)))
 }
 
-fn hello_world() -> impl Display {
-    lazy_format!("{}, {w}!", get_hello(), w = get_world())
-}
+assert_eq!(repeated("ab".to_owned(), 3).to_string(), "ababab");
 
-let result = hello_world();
+let words = vec!["a".to_owned(), "b".to_owned()];
+let joined = make_lazy_format!(ref |f| words.iter().try_for_each(|word| f.write_str(word)));
+assert_eq!(joined.to_string(), "ab");
 
-// get_hello and get_world aren't called until the object is
-// formatted into a String.
-let result_str = result.to_string();
-assert_eq!(result_str, "Hello, World!");
+// `words` was only borrowed
+assert_eq!(words.len(), 2);
 ```
 
-Just like with regular formatting, `lazy_format` can automatically, implicitly
-capture named parameters:
+By default, only [`Display`] (and [`Debug`]) are implemented. Implementations
+of the other [formatting traits], like [`LowerExp`] and [`UpperExp`], can be
+added by providing an additional closure for each of them:
 
 ```
-use std::mem::{size_of_val, size_of};
-use lazy_format::lazy_format;
+use lazy_format::make_lazy_format;
 
-let a = 10;
-let b = 20;
+let value = 1234.5;
 
-let result = lazy_format!("{a} {b}");
-assert_eq!(size_of_val(&result), size_of::<i32>() * 2);
-assert_eq!(result.to_string(), "10 20");
+let meters = make_lazy_format!(
+    |f| write!(f, "{}m", value),
+    LowerExp => |f| write!(f, "{:e}m", value),
+    UpperExp => |f| write!(f, "{:E}m", value),
+);
+
+assert_eq!(format!("{}", meters), "1234.5m");
+assert_eq!(format!("{:e}", meters), "1.2345e3m");
+assert_eq!(format!("{:E}", meters), "1.2345E3m");
 ```
 
-# Demonstation of lazy capturing:
+Lazy formats are `Copy` when their captures are, and references to them are
+also [`Display`] (through the standard library's blanket `impl Display for
+&T`), so they can be passed by reference anywhere a `Display` is expected,
+including as a `&dyn Display` or to [`format_args!`]:
 
 ```
 use std::fmt::Display;
-use std::mem::{size_of_val, size_of};
-use lazy_format::lazy_format;
+use lazy_format::make_lazy_format;
 
+let greeting = make_lazy_format!(|f| f.write_str("Hello"));
 
-fn get_formatted() -> impl Display {
-    let a: isize = 10;
-    let b: isize = 15;
+let dynamic: &dyn Display = &greeting;
+assert_eq!(format!("{}, World!", format_args!("{}", &greeting)), "Hello, World!");
+assert_eq!(dynamic.to_string(), "Hello");
+```
 
-    lazy_format!("10 + 15: {v}, again: {v}", v = (a + b))
-}
+The lazy format is a `#[repr(transparent)]` wrapper around the closure, so
+it has exactly the size and alignment of the closure, which is the size of
+its captures. The compiler lays out those captures like the fields of a
+struct, so it may reorder them to reduce padding, but any padding needed
+for alignment (such as after a `u8` captured alongside a `u64`) remains:
 
-let result = get_formatted();
+```
+use std::mem::size_of_val;
+use lazy_format::make_lazy_format;
 
-// The result captures 2 isize values (a and b) from get_formatted.
-assert_eq!(size_of_val(&result), size_of::<isize>() * 2);
+let (a, b, c) = (1u8, 2u32, 3u8);
 
-let result_str = result.to_string();
-assert_eq!(result_str, "10 + 15: 25, again: 25");
+let packed = make_lazy_format!(move |f| write!(f, "{a} {b} {c}"));
+assert_eq!(size_of_val(&packed), 8);
+
+let closure = move |f: &mut std::fmt::Formatter| write!(f, "{a} {b} {c}");
+assert_eq!(size_of_val(&packed), size_of_val(&closure));
 ```
 
-# Conditional formatting
+Because a lazy format does nothing until it's formatted, it's `#[must_use]`,
+so creating one and then not using it (perhaps expecting it to print
+something) causes a warning:
 
-`lazy_format!` supports conditional formatting with `match`- or `if`-
-style syntax. When doing a conditional format, add the formatting pattern
-and arguments directly into the `match` arms or `if` blocks, rather than
-code; this allows conditional formatting to still be captured in a single
-static type.
+```compile_fail
+#![deny(unused_must_use)]
+use lazy_format::make_lazy_format;
+
+make_lazy_format!(|f| f.write_str("Hello"));
+```
+
+[`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+[`Display::fmt`]: https://doc.rust-lang.org/core/fmt/trait.Display.html#tymethod.fmt
+[`Debug`]: https://doc.rust-lang.org/std/fmt/trait.Debug.html
+[`fmt::Result`]: https://doc.rust-lang.org/std/fmt/type.Result.html
+[`LowerExp`]: https://doc.rust-lang.org/std/fmt/trait.LowerExp.html
+[`UpperExp`]: https://doc.rust-lang.org/std/fmt/trait.UpperExp.html
+[`format_args!`]: https://doc.rust-lang.org/std/macro.format_args.html
+[formatting traits]: https://doc.rust-lang.org/std/fmt/index.html#formatting-traits
+*/
+#[macro_export]
+macro_rules! make_lazy_format {
+    // The closure is always a `move` closure, unless it's marked with `ref`.
+    // `$keyword` is whatever the caller wrote before the closure, so that the
+    // `Debug` implementation can show the original invocation.
+    (|$fmt:ident| $($rest:tt)+) => {
+        $crate::make_lazy_format!(@closure [move] [] |$fmt| $($rest)+)
+    };
+
+    (move |$fmt:ident| $($rest:tt)+) => {
+        $crate::make_lazy_format!(@closure [move] [move] |$fmt| $($rest)+)
+    };
+
+    (ref |$fmt:ident| $($rest:tt)+) => {
+        $crate::make_lazy_format!(@closure [] [ref] |$fmt| $($rest)+)
+    };
+
+    (@closure [$($move:tt)?] [$($keyword:tt)?] |$fmt:ident| $write:expr) => {{
+        #[derive(Clone, Copy)]
+        #[repr(transparent)]
+        #[must_use = "lazy formats do nothing until they're formatted"]
+        struct LazyFormat<F: Fn(&mut ::core::fmt::Formatter) -> ::core::fmt::Result>(F);
+
+        // The alternate Debug (`{:#?}`) actually renders the value, which is
+        // useful with `dbg!`; the regular one is free and just prints the
+        // macro invocation.
+        impl<F: Fn(&mut ::core::fmt::Formatter) -> ::core::fmt::Result> ::core::fmt::Debug
+            for LazyFormat<F>
+        {
+            $crate::fmt_inline! {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    if f.alternate() {
+                        (self.0)(f)
+                    } else {
+                        f.write_str(concat!(
+                            "make_lazy_format!(",
+                            stringify!($($keyword)? |$fmt| $write),
+                            ")"
+                        ))
+                    }
+                }
+            }
+        }
+
+        impl<F: Fn(&mut ::core::fmt::Formatter) -> ::core::fmt::Result> ::core::fmt::Display
+            for LazyFormat<F>
+        {
+            $crate::fmt_inline! {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    (self.0)(f)
+                }
+            }
+        }
+
+        $crate::impl_rendered_cmp!(
+            LazyFormat [F: Fn(&mut ::core::fmt::Formatter) -> ::core::fmt::Result]
+        );
+
+        $crate::impl_serialize!(
+            LazyFormat [F: Fn(&mut ::core::fmt::Formatter) -> ::core::fmt::Result]
+        );
+
+        LazyFormat($($move)? |$fmt: &mut ::core::fmt::Formatter| -> ::core::fmt::Result {
+            // The closure may never return, like `|f| todo!()`
+            #[allow(unreachable_code)]
+            return $crate::IntoFmtResult::into_fmt_result($write);
+        })
+    }};
+
+    // Lazy format with additional formatting traits. There's still only one
+    // closure, which is told which trait it's being formatted with.
+    (
+        @closure [$($move:tt)?] [$($keyword:tt)?]
+        |$fmt:ident| $write:expr,
+        $($trait:ident => |$trait_fmt:ident| $trait_write:expr),+ $(,)?
+    ) => {{
+        #[derive(Clone, Copy)]
+        enum Trait {
+            Display,
+            $($trait,)+
+        }
+
+        #[derive(Clone, Copy)]
+        #[repr(transparent)]
+        #[must_use = "lazy formats do nothing until they're formatted"]
+        struct LazyFormat<F: Fn(&mut ::core::fmt::Formatter, Trait) -> ::core::fmt::Result>(F);
+
+        impl<F: Fn(&mut ::core::fmt::Formatter, Trait) -> ::core::fmt::Result> ::core::fmt::Debug
+            for LazyFormat<F>
+        {
+            $crate::fmt_inline! {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    if f.alternate() {
+                        (self.0)(f, Trait::Display)
+                    } else {
+                        f.write_str(concat!(
+                            "make_lazy_format!(",
+                            stringify!($($keyword)? |$fmt| $write, $($trait => |$trait_fmt| $trait_write),+),
+                            ")"
+                        ))
+                    }
+                }
+            }
+        }
+
+        impl<F: Fn(&mut ::core::fmt::Formatter, Trait) -> ::core::fmt::Result> ::core::fmt::Display
+            for LazyFormat<F>
+        {
+            $crate::fmt_inline! {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    (self.0)(f, Trait::Display)
+                }
+            }
+        }
+
+        $crate::impl_rendered_cmp!(
+            LazyFormat [F: Fn(&mut ::core::fmt::Formatter, Trait) -> ::core::fmt::Result]
+        );
+
+        $crate::impl_serialize!(
+            LazyFormat [F: Fn(&mut ::core::fmt::Formatter, Trait) -> ::core::fmt::Result]
+        );
+
+        $(
+            impl<F: Fn(&mut ::core::fmt::Formatter, Trait) -> ::core::fmt::Result> ::core::fmt::$trait
+                for LazyFormat<F>
+            {
+                $crate::fmt_inline! {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                        (self.0)(f, Trait::$trait)
+                    }
+                }
+            }
+        )+
+
+        LazyFormat($($move)? |f: &mut ::core::fmt::Formatter, t: Trait| -> ::core::fmt::Result {
+            match t {
+                Trait::Display => {
+                    let $fmt = f;
+                    #[allow(unreachable_code)]
+                    return $crate::IntoFmtResult::into_fmt_result($write);
+                }
+                $(Trait::$trait => {
+                    let $trait_fmt = f;
+                    #[allow(unreachable_code)]
+                    return $crate::IntoFmtResult::into_fmt_result($trait_write);
+                })+
+            }
+        })
+    }};
+}
+
+/**
+Like [`make_lazy_format!`], but the closure returns a `Result` with a custom
+error type, instead of a [`fmt::Result`]. Because a [`Display`]
+implementation can only fail with a [`fmt::Error`], when the closure fails,
+its error is stashed in the lazy format (a [`TryFormat`]) and a
+[`fmt::Error`] is returned to the formatter. The original error can then be
+retrieved with [`TryFormat::take_error`].
+
+The closure's return type must be written explicitly. To use `?` with
+`write!` in the closure, the error type must implement
+`From<fmt::Error>`. To create a [`TryFormat`] from a closure that borrows
+its captures, or one that was built somewhere else, use
+[`fallible`][crate::fallible].
+
+# Example
+
+```
+use std::fmt::{self, Write};
+use lazy_format::make_lazy_try_format;
+
+#[derive(Debug, PartialEq)]
+enum RenderError {
+    Fmt,
+    Negative(i32),
+}
+
+impl From<fmt::Error> for RenderError {
+    fn from(_: fmt::Error) -> Self {
+        RenderError::Fmt
+    }
+}
+
+let values = [1, 2, -3, 4];
+
+let sum = make_lazy_try_format!(|f| -> Result<(), RenderError> {
+    for &value in &values {
+        if value < 0 {
+            return Err(RenderError::Negative(value));
+        }
+        write!(f, "{} ", value)?;
+    }
+    Ok(())
+});
+
+let mut dest = String::new();
+assert!(write!(&mut dest, "{}", sum).is_err());
+assert_eq!(sum.take_error(), Some(RenderError::Negative(-3)));
+assert_eq!(sum.take_error(), None);
+```
+
+[`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+[`fmt::Result`]: https://doc.rust-lang.org/std/fmt/type.Result.html
+[`fmt::Error`]: https://doc.rust-lang.org/std/fmt/struct.Error.html
+*/
+#[macro_export]
+macro_rules! make_lazy_try_format {
+    (|$fmt:ident| -> $ret:ty $body:block) => {
+        $crate::TryFormat::new(move |$fmt: &mut ::core::fmt::Formatter| -> $ret { $body })
+    };
+}
+
+/**
+Lazily format something. Essentially the same as [`format!`], except that
+instead of formatting its arguments to a string, it captures them in an opaque
+struct, which can be formatted later. This allows you to build up formatting
+operations without any intermediary allocations or extra formatting calls. Also
+supports lazy conditional and looping constructs.
+
+The return value of this macro is left deliberately unspecified and
+undocumented. The most important this about it is its [`Display`]
+implementation, which executes the deferred formatting operation. It also
+provides a [`Debug`] implementation, which simply prints the
+[`lazy_format!`]`(...)` call without evaluating any of its arguments (the
+alternate form, `{:#?}`, instead executes the formatting operation, so that
+the actual content can be seen with [`dbg!`]), as well as [`Clone`] and
+[`Copy`] if those traits are available in the captured context. If the
+`alloc` feature is enabled, it also implements [`PartialEq`], [`Eq`],
+[`PartialOrd`], and [`Ord`] by comparing the rendered output; see
+[`LazyFormatExt::cmp_rendered`] for the costs of this.
+
+Note that this macro is completely lazy; it captures the expressions to
+be formatted in the struct and doesn't evaluate them until the struct is
+actually written to a [`String`] or [`File`] or or other writable destination.
+This means that the argument expression will be evaluated *every* time the
+instance is written, which may not be what you want; be sure to eagerly perform
+any 1-time calculations you want to before calling `lazy_format!`.
+
+# Basic example:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn get_hello() -> String {
+    String::from("Hello")
+}
+
+fn get_world() -> String {
+    String::from("World")
+}
+
+fn hello_world() -> impl Display {
+    lazy_format!("{}, {w}!", get_hello(), w = get_world())
+}
+
+let result = hello_world();
+
+// get_hello and get_world aren't called until the object is
+// formatted into a String.
+let result_str = result.to_string();
+assert_eq!(result_str, "Hello, World!");
+```
+
+Just like with regular formatting, `lazy_format` can automatically, implicitly
+capture named parameters:
+
+```
+use std::mem::{size_of_val, size_of};
+use lazy_format::lazy_format;
+
+let a = 10;
+let b = 20;
+
+let result = lazy_format!("{a} {b}");
+assert_eq!(size_of_val(&result), size_of::<i32>() * 2);
+assert_eq!(result.to_string(), "10 20");
+```
+
+Like a string, `lazy_format!("{}", value)` is truncated to the precision of
+the formatter it's written to, if any. Every other form, including any
+other basic format string, as well as [`make_lazy_format!`], ignores the
+precision (though the closure passed to [`make_lazy_format!`] can inspect
+it itself). Use [`transform::truncate`][crate::transform::truncate] to
+truncate any lazy format:
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::truncate;
+
+let name = "World";
+let greeting = lazy_format!("{}", name);
+assert_eq!(format!("{:.3}", greeting), "Wor");
+
+let greeting = lazy_format!("Hello, {name}!");
+assert_eq!(format!("{:.8}", greeting), "Hello, World!");
+assert_eq!(truncate(greeting, 8, "").to_string(), "Hello, W");
+```
+
+Like the closure in [`make_lazy_format!`], all of the arguments are moved
+into the lazy format, including any variables used by an argument like
+`&name`. To borrow a variable or one of its fields instead, so that it can
+still be used afterwards, write the argument as `ref name` or
+`ref user.name` (or `key = ref name`):
+
+```
+use lazy_format::lazy_format;
+
+let names = vec!["Alice".to_owned(), "Bob".to_owned()];
+let count = names.len();
+
+let result = lazy_format!("{} names: {:?}", count, ref names);
+assert_eq!(result.to_string(), "2 names: [\"Alice\", \"Bob\"]");
+
+// `names` was only borrowed
+assert_eq!(names.len(), 2);
+```
+
+# Lazy formats in methods
+
+A lazy format can be returned from a method that takes `&self`, as an
+`impl Display + '_`. Since `self` is a reference, it's the reference that's
+moved into the lazy format, and the fields of `self` can be used as
+arguments directly. However, a field that's matched on or looped over is
+still used by value, which isn't allowed through a reference (just like in
+a regular `match` or `for` loop); borrow it with `&` instead, as in
+`match (&self.field)` or `for item in &self.field`:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+struct User {
+    name: String,
+    nickname: Option<String>,
+    roles: Vec<String>,
+}
+
+impl User {
+    fn display_name(&self) -> impl Display + '_ {
+        lazy_format!(match (&self.nickname) {
+            Some(nickname) => ("{nickname} ({})", self.name),
+            None => ("{}", self.name),
+        })
+    }
+
+    fn roles(&self) -> impl Display + '_ {
+        lazy_format!("{role}" sep ", " for role in &self.roles)
+    }
+}
+
+let user = User {
+    name: "Alice".to_owned(),
+    nickname: Some("Al".to_owned()),
+    roles: vec!["admin".to_owned(), "editor".to_owned()],
+};
+
+assert_eq!(user.display_name().to_string(), "Al (Alice)");
+assert_eq!(user.roles().to_string(), "admin, editor");
+```
+
+In a method that takes `&mut self`, the `self` reference can't be copied,
+so a lazy format that uses `self` keeps it borrowed mutably for as long as
+the lazy format exists. Use `ref self.field` arguments to borrow just the
+fields that are needed instead:
+
+```
+use lazy_format::lazy_format;
+
+struct Log {
+    name: String,
+    lines: Vec<String>,
+}
+
+impl Log {
+    fn push(&mut self, line: &str) {
+        let entry = lazy_format!("[{}] {line}", ref self.name);
+        self.lines.push(entry.to_string());
+    }
+}
+
+let mut log = Log { name: "main".to_owned(), lines: Vec::new() };
+log.push("started");
+assert_eq!(log.lines, ["[main] started"]);
+```
+
+# Demonstation of lazy capturing:
+
+```
+use std::fmt::Display;
+use std::mem::{size_of_val, size_of};
+use lazy_format::lazy_format;
+
+
+fn get_formatted() -> impl Display {
+    let a: isize = 10;
+    let b: isize = 15;
+
+    lazy_format!("10 + 15: {v}, again: {v}", v = (a + b))
+}
+
+let result = get_formatted();
+
+// The result captures 2 isize values (a and b) from get_formatted.
+assert_eq!(size_of_val(&result), size_of::<isize>() * 2);
+
+let result_str = result.to_string();
+assert_eq!(result_str, "10 + 15: 25, again: 25");
+```
+
+# Padding
+
+To write a single value, padded on the right with a fill char up to a
+width, use `pad`, followed by the value, the width, and the fill char. This
+works with any [`Display`] value, including other lazy formats, and the
+width is in chars. See [`transform::column`] for other alignments:
+
+```
+use lazy_format::lazy_format;
+
+let name = "Total";
+let amount = 42;
+
+let line = lazy_format!("{}{amount}", lazy_format!(pad name, 10, '.'));
+assert_eq!(line.to_string(), "Total.....42");
+
+let nested = lazy_format!(pad lazy_format!("{amount}%"), 5, '_');
+assert_eq!(nested.to_string(), "42%__");
+```
+
+[`transform::column`]: crate::transform::column()
+
+# Evaluating once per format
+
+Because the arguments are evaluated every time the value is formatted, an
+expression that's used in several places, such as in several branches of a
+conditional format, would be evaluated several times. Use `once` to evaluate
+an expression exactly once each time the value is formatted, and bind it to
+a name (or any irrefutable pattern) for the rest of the format:
+
+```
+use std::cell::Cell;
+use lazy_format::lazy_format;
+
+let counter = Cell::new(0);
+let next = || {
+    counter.set(counter.get() + 1);
+    counter.get()
+};
+
+let result = lazy_format!(
+    once n = next();
+    if n % 2 == 0 => ("{n} is even")
+    else => ("{n} is odd")
+);
+
+assert_eq!(result.to_string(), "1 is odd");
+assert_eq!(result.to_string(), "2 is even");
+assert_eq!(counter.get(), 2);
+```
+
+Several `once` bindings can be chained, and later ones can refer to earlier
+ones. The bindings are evaluated in the same closure as the rest of the
+format, so it can use any other captured variable, like a `String`, just
+like it could without `once`. `ref` arguments aren't supported after `once`.
+
+# Conditional formatting
+
+`lazy_format!` supports conditional formatting with `match`- or `if`-
+style syntax. When doing a conditional format, add the formatting pattern
+and arguments directly into the `match` arms or `if` blocks, rather than
+code; this allows conditional formatting to still be captured in a single
+static type.
+
+Each arm's format string and arguments are passed unchanged to
+[`format_args!`], so they're checked at compile time just like with
+[`format!`], and any errors point at the offending arm. For example, this
+fails to compile with an error pointing at the `("{} {}", value)` arm:
+
+```compile_fail
+use lazy_format::lazy_format;
+
+let number = 10;
+let result = lazy_format!(match (number) {
+    0 => "Zero",
+    value => ("{} {}", value),
+});
+```
+
+Likewise, a named placeholder that doesn't refer to anything is reported at
+the placeholder:
+
+```compile_fail
+use lazy_format::lazy_format;
+
+let number = 10;
+let result = lazy_format!(if number > 5 => "Large: {numbr}" else => "Small");
+```
+
+## `match` conditional example:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn get_number(num: usize) -> impl Display {
+    // Note that the parenthesis in the match conditional are required,
+    // due to limitations in Rust's macro parsing (can't follow an
+    // expression with `{}`)
+    lazy_format!(match (num) {
+        0 => "Zero",
+        1 => "One",
+        2 => "Two",
+        3 => "Three",
+        4 | 5 => "Four or five",
+        value if value % 2 == 0 => ("A large even number: {}", value),
+        value => "An unrecognized number: {value}",
+    })
+}
+
+assert_eq!(get_number(0).to_string(), "Zero");
+assert_eq!(get_number(1).to_string(), "One");
+assert_eq!(get_number(2).to_string(), "Two");
+assert_eq!(get_number(3).to_string(), "Three");
+assert_eq!(get_number(4).to_string(), "Four or five");
+assert_eq!(get_number(5).to_string(), "Four or five");
+assert_eq!(get_number(6).to_string(), "A large even number: 6");
+assert_eq!(get_number(7).to_string(), "An unrecognized number: 7");
+```
+
+A parenthesized format in a conditional arm or loop takes the same arguments
+as [`format!`], including explicit positional arguments (like `{0}`) and
+named arguments computed from expressions:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn sign(value: i32) -> &'static str {
+    if value < 0 { "negative" } else { "positive" }
+}
+
+fn describe(value: i32) -> impl Display {
+    lazy_format!(match (value) {
+        0 => "zero",
+        1 => ("{0} ({0:?})", "one"),
+        value => ("{name}: {abs}", name = sign(value), abs = value.abs()),
+    })
+}
+
+assert_eq!(describe(0).to_string(), "zero");
+assert_eq!(describe(1).to_string(), "one (\"one\")");
+assert_eq!(describe(-3).to_string(), "negative: 3");
+```
+
+Match guards can be any guard supported by `match`. On versions of Rust
+that support them, this includes `if let` guards and `let` chains; a guard
+that uses `let` has to start with it (`if x && let ...` isn't supported, but
+`if let ... && x` is). On older versions, the same thing can be written with
+a plain `match` and a nested `if let`:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn describe(value: &str) -> impl Display + '_ {
+    lazy_format!(match (value) {
+        "" => "Nothing",
+        s => @ lazy_format!(
+            if let Ok(n) = s.parse::<i32>() => ("A {}number: {}", if n < 0 { "negative " } else { "" }, n)
+            else => ("Something else: {}", s)
+        ),
+    })
+}
+
+assert_eq!(describe("").to_string(), "Nothing");
+assert_eq!(describe("-5").to_string(), "A negative number: -5");
+assert_eq!(describe("12").to_string(), "A number: 12");
+assert_eq!(describe("abc").to_string(), "Something else: abc");
+```
+
+Because the `match` is an ordinary Rust `match`, its arms must be
+exhaustive; if they aren't, the compiler will report a "non-exhaustive
+patterns" error on the condition. As a more readable alternative to a
+trailing `_` arm, the last arm can use the `default` keyword. `default` is
+only a keyword in the last arm; in any other arm it's an ordinary binding:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn ordinal(num: usize) -> impl Display {
+    lazy_format!(match (num % 10) {
+        1 if num % 100 != 11 => "{num}st",
+        2 if num % 100 != 12 => "{num}nd",
+        3 if num % 100 != 13 => "{num}rd",
+        default => "{num}th",
+    })
+}
+
+assert_eq!(ordinal(1).to_string(), "1st");
+assert_eq!(ordinal(12).to_string(), "12th");
+assert_eq!(ordinal(23).to_string(), "23rd");
+assert_eq!(ordinal(100).to_string(), "100th");
+```
+
+An arm can also write any other [`Display`] value, like another lazy
+format, by writing `@` and an expression instead of a format. The value is
+formatted with the same [`Formatter`], so it sees the same width, precision,
+and other flags:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn describe_list(items: &[i32]) -> impl Display + '_ {
+    let listing = lazy_format!("{item}" sep ", " for item in items);
+
+    lazy_format!(match (items.len()) {
+        0 => "Empty",
+        1 => @ items[0],
+        default => @ listing,
+    })
+}
+
+assert_eq!(describe_list(&[]).to_string(), "Empty");
+assert_eq!(describe_list(&[5]).to_string(), "5");
+assert_eq!(describe_list(&[1, 2, 3]).to_string(), "1, 2, 3");
+```
+
+To match on several values at once, separate them with commas; they're
+matched together as a tuple, so each arm's pattern is a tuple pattern. Guards
+and `|` patterns work across the whole tuple, and like any `match`, the arms
+are checked for exhaustiveness. Since the values are moved into the tuple,
+use references for values that aren't [`Copy`]:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn describe_point(x: i32, y: i32) -> impl Display {
+    lazy_format!(match (x, y) {
+        (0, 0) => "The origin",
+        (x, 0) | (0, x) if x < 0 => ("On a negative axis at {}", x),
+        (x, 0) => ("On the x axis at {}", x),
+        (0, y) => ("On the y axis at {}", y),
+        (x, y) => "At {x}, {y}",
+    })
+}
+
+assert_eq!(describe_point(0, 0).to_string(), "The origin");
+assert_eq!(describe_point(0, -2).to_string(), "On a negative axis at -2");
+assert_eq!(describe_point(3, 0).to_string(), "On the x axis at 3");
+assert_eq!(describe_point(0, 4).to_string(), "On the y axis at 4");
+assert_eq!(describe_point(3, 4).to_string(), "At 3, 4");
+```
+
+```compile_fail
+use lazy_format::lazy_format;
+
+let (x, y) = (true, false);
+
+// error: non-exhaustive patterns: `(false, false)` not covered
+let result = lazy_format!(match (x, y) {
+    (true, _) => "x",
+    (false, true) => "y",
+});
+```
+
+Exhaustiveness checking works just like it does for any other `match`, so
+a `match` on an uninhabited type, like an empty enum, can have no arms at
+all. On Rust 1.82 and later, arms for uninhabited variants can also be left
+out. This is mostly useful in generic code:
+
+```
+use std::convert::Infallible;
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn never(value: Infallible) -> impl Display {
+    lazy_format!(match (value) {})
+}
+
+fn parsed(value: Result<u32, Infallible>) -> impl Display {
+    lazy_format!(match (value) {
+        Ok(n) => "Parsed {n}",
+        Err(err) => @ lazy_format!(match (err) {}),
+    })
+}
+
+assert_eq!(parsed(Ok(5)).to_string(), "Parsed 5");
+```
+
+Alternatively, use `match?` to add an implicit catch-all arm that writes
+nothing, similar to an `if` without an `else`:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn small_number(num: usize) -> impl Display {
+    lazy_format!(match? (num) {
+        1 => "one",
+        2 => "two",
+        3 => "three",
+    })
+}
+
+assert_eq!(small_number(2).to_string(), "two");
+assert_eq!(small_number(10).to_string(), "");
+```
+
+## `if` conditional example:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn describe_number(value: isize) -> impl Display {
+    lazy_format!(
+        if value < 0 => ("A negative number: {}", value)
+        else if value % 3 == 0 => ("A number divisible by 3: {}", value)
+        else if value % 2 == 1 => ("An odd number: {}", value)
+        else => "Some other kind of number"
+    )
+}
+
+assert_eq!(describe_number(-2).to_string(), "A negative number: -2");
+assert_eq!(describe_number(-1).to_string(), "A negative number: -1");
+assert_eq!(describe_number(0).to_string(), "A number divisible by 3: 0");
+assert_eq!(describe_number(1).to_string(), "An odd number: 1");
+assert_eq!(describe_number(2).to_string(), "Some other kind of number");
+assert_eq!(describe_number(3).to_string(), "A number divisible by 3: 3");
+```
+
+`if` formatters are allowed to exclude the final `else` branch, in which
+case `lazy_format` will simply write an empty string:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn only_evens(value: i32) -> impl Display {
+    lazy_format!(if value % 2 == 0 => ("An even number: {}", value))
+}
+
+assert_eq!(only_evens(10).to_string(), "An even number: 10");
+assert_eq!(only_evens(5).to_string(), "");
+```
+
+## `if let` conditional example:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn describe_optional_number(value: Option<isize>) -> impl Display {
+    lazy_format!(
+        if let Some(10) = value => "It's ten!"
+        else if let Some(3) | Some(4) = value => "It's three or four!"
+        else if let | Some(0) = value => "It's zero!"
+        else if let Some(x) = value => ("It's some other value: {}", x)
+        else => "It's not a number!"
+    )
+}
+
+assert_eq!(describe_optional_number(Some(10)).to_string(), "It's ten!");
+assert_eq!(describe_optional_number(Some(3)).to_string(), "It's three or four!");
+assert_eq!(describe_optional_number(Some(4)).to_string(), "It's three or four!");
+assert_eq!(describe_optional_number(Some(0)).to_string(), "It's zero!");
+assert_eq!(describe_optional_number(Some(5)).to_string(), "It's some other value: 5");
+assert_eq!(describe_optional_number(None).to_string(), "It's not a number!");
+```
+
+The final `else` branch of an `if let` chain can also bind a value with an
+irrefutable `else let`, so that the fallback output can make use of it:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn describe_pair(pair: (i32, i32)) -> impl Display {
+    lazy_format!(
+        if let (0, 0) = pair => "The origin"
+        else if let (x, 0) = pair => ("On the x axis at {}", x)
+        else let (x, y) = pair => ("At {}, {}", x, y)
+    )
+}
+
+assert_eq!(describe_pair((0, 0)).to_string(), "The origin");
+assert_eq!(describe_pair((3, 0)).to_string(), "On the x axis at 3");
+assert_eq!(describe_pair((3, 4)).to_string(), "At 3, 4");
+```
+
+## `option` conditional example:
+
+Formatting an [`Option`], with a fallback for [`None`], is common enough that
+it has its own syntax. The value in the [`Some`] is passed as the first
+positional argument to the format string, before any other arguments:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn describe_score(score: Option<u32>) -> impl Display {
+    lazy_format!(option score => ("{} / {}", 100) else "N/A")
+}
+
+assert_eq!(describe_score(Some(87)).to_string(), "87 / 100");
+assert_eq!(describe_score(None).to_string(), "N/A");
+```
+
+Alternatively, the value can be bound with a name (or any pattern) with
+`option (...) as`, in which case it's not passed as an argument:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn describe_user(name: Option<&str>) -> impl Display + '_ {
+    lazy_format!(option (name) as name => "User: {name}" else "Anonymous")
+}
+
+assert_eq!(describe_user(Some("Nathan")).to_string(), "User: Nathan");
+assert_eq!(describe_user(None).to_string(), "Anonymous");
+```
+
+To format an [`Option`] that's already holding a value to display, such as
+a lazy format built elsewhere, use [`transform::opt_display`].
+
+[`Option`]: https://doc.rust-lang.org/std/option/enum.Option.html
+[`Some`]: https://doc.rust-lang.org/std/option/enum.Option.html#variant.Some
+[`None`]: https://doc.rust-lang.org/std/option/enum.Option.html#variant.None
+
+## `result` conditional example:
+
+A [`Result`] can be formatted the same way, with `result`. The value in the
+[`Ok`] is passed as the first positional argument to the first format
+string, and the error in the [`Err`] to the second:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn describe_parse(input: &str) -> impl Display + '_ {
+    lazy_format!(result input.parse::<i32>() => ("{} (from {input:?})") else "error: {}")
+}
+
+assert_eq!(describe_parse("12").to_string(), "12 (from \"12\")");
+assert_eq!(describe_parse("x").to_string(), "error: invalid digit found in string");
+```
+
+Or, with `result (...) as`, both values can be bound with a name (or any
+pattern):
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn describe_lookup(result: Result<(u32, &str), u32>) -> impl Display + '_ {
+    lazy_format!(
+        result (result) as (id, name) => ("{}: {name}", id)
+        else code => "error {code}"
+    )
+}
+
+assert_eq!(describe_lookup(Ok((1, "Nathan"))).to_string(), "1: Nathan");
+assert_eq!(describe_lookup(Err(404)).to_string(), "error 404");
+```
+
+## `show` conditional example:
+
+For toggling between two small bits of output based on a `bool`, such as
+checkboxes or flags, there's a terse `show` syntax. Unlike `if`, the `else`
+branch is required:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn checkbox(label: &str, checked: bool) -> impl Display + '_ {
+    lazy_format!("{} {label}", lazy_format!(show checked => "[x]" else "[ ]"))
+}
+
+assert_eq!(checkbox("Done", true).to_string(), "[x] Done");
+assert_eq!(checkbox("Todo", false).to_string(), "[ ] Todo");
+```
+
+Several flags can be checked in turn with `else show`; the first one that's
+true is used, and the final `else` is the fallback:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn status(failed: bool, running: bool) -> impl Display {
+    lazy_format!(show failed => "FAIL" else show running => "...." else "PASS")
+}
+
+assert_eq!(status(true, true).to_string(), "FAIL");
+assert_eq!(status(false, true).to_string(), "....");
+assert_eq!(status(false, false).to_string(), "PASS");
+```
+
+# Looping formatting
+
+`lazy_format!` supports formatting elements in a collection with a loop:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+let list = vec![1i32, 2, 3, 4];
+let list_ref = &list;
+
+// Format each element in the iterable without additional arguments to `format_args`
+let simple_semicolons = lazy_format!("{v}; " for v in list_ref.iter().map(|x| x - 1));
+assert_eq!(simple_semicolons.to_string(), "0; 1; 2; 3; ");
+
+// Perform a full format with additional arguments on each element in the iterable.
+let header = "Value";
+let full_format = lazy_format!(("{}: {}; ", header, v) for v in list_ref);
+assert_eq!(full_format.to_string(), "Value: 1; Value: 2; Value: 3; Value: 4; ");
+```
+
+Like the arms of a conditional format, the element format and its arguments
+are passed unchanged to [`format_args!`], so a mismatch between the
+placeholders and the arguments is reported at the parenthesized element
+format, with the placeholders and arguments highlighted, rather than
+somewhere inside the macro. This is true for every kind of loop, including
+loops with `sep`, `wrap`, or `enumerate`, and loops nested in conditional
+arms. For example, both of these fail to compile with an error pointing at
+the `("{}: {}; ", ...)` tuple:
+
+```compile_fail
+use lazy_format::lazy_format;
+
+let list = [1, 2, 3];
+
+// error: 2 positional arguments in format string, but there is 1 argument
+let result = lazy_format!(("{}: {}; ", v) for v in list);
+```
+
+```compile_fail
+use lazy_format::lazy_format;
+
+let list = [1, 2, 3];
+let header = "Value";
+
+// error: argument never used
+let result = lazy_format!(("{}: {}; ", header, v, v) sep ", " for (i, v) in enumerate list);
+```
+
+The collection expression is evaluated every time the value is formatted,
+and like all captured variables, the collection is moved into the lazy
+format. Collections of [`Copy`] values, like arrays of integers, can be
+looped over directly, because they're copied each time. Other collections,
+like [`Vec`], have to be looped over by reference, with `&collection` (which
+moves the collection itself into the lazy format, and loops over references
+to its elements) or with a reference created outside of the lazy format (to
+keep using the collection afterwards):
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn numbered(lines: Vec<String>) -> impl Display {
+    lazy_format!(("{} ", line.len()) for line in &lines)
+}
+
+assert_eq!(numbered(vec!["a".into(), "bcd".into()]).to_string(), "1 3 ");
+
+let array = [1, 2, 3];
+assert_eq!(lazy_format!("{v} " for v in array).to_string(), "1 2 3 ");
+
+let list = vec![1, 2, 3];
+let list_ref = &list;
+assert_eq!(lazy_format!("{v} " for v in list_ref).to_string(), "1 2 3 ");
+assert_eq!(list.len(), 3);
+```
+
+Use `sep` to write a separator between each element (but not before the
+first or after the last), and `wrap` to write a prefix and suffix around the
+whole list. The prefix and suffix are written even if the collection is
+empty. These can be used separately or together, and like the element
+format, they can be either a literal or a parenthesized format with
+arguments:
+
+```
+use lazy_format::lazy_format;
+
+let list = vec![1i32, 2, 3, 4];
+let list_ref = &list;
+
+let comma_separated = lazy_format!("{v}" sep ", " for v in list_ref);
+assert_eq!(comma_separated.to_string(), "1, 2, 3, 4");
+
+let bracketed = lazy_format!("{v}" wrap "[" "]" sep ", " for v in list_ref);
+assert_eq!(bracketed.to_string(), "[1, 2, 3, 4]");
+
+let empty: [i32; 0] = [];
+let bracketed = lazy_format!("{v}" wrap "[" "]" sep ", " for v in empty);
+assert_eq!(bracketed.to_string(), "[]");
+```
+
+Since the loop binding can be any irrefutable pattern, maps can be
+formatted by destructuring each key/value pair. The elements are formatted in
+whatever order the collection's iterator yields them, so a [`HashMap`] is
+formatted in an arbitrary order; use a [`BTreeMap`] for sorted output:
+
+```
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn query_string<'a>(params: &'a BTreeMap<&str, i32>) -> impl Display + 'a {
+    lazy_format!("{key}={value}" sep "&" for (key, value) in params)
+}
+
+let params = BTreeMap::from([("b", 2), ("a", 1)]);
+assert_eq!(query_string(&params).to_string(), "a=1&b=2");
+```
+
+To format at most a certain number of elements, add `take` and a limit
+after the collection, optionally followed by `else` and an overflow format
+that is written (before the suffix) only if the collection had more elements
+than the limit. Because of how `macro_rules` parses expressions, the
+collection must be a single identifier or parenthesized when using `take`:
+
+```
+use lazy_format::lazy_format;
+
+let list = vec![1i32, 2, 3, 4, 5];
+let list_ref = &list;
+
+let short = lazy_format!("{v}" sep ", " for v in list_ref take 3 else ", ...");
+assert_eq!(short.to_string(), "1, 2, 3, ...");
+
+let all = lazy_format!("{v}" sep ", " for v in list_ref take 5 else ", ...");
+assert_eq!(all.to_string(), "1, 2, 3, 4, 5");
+
+let limit = 2;
+let evens = lazy_format!("{v} " for v in (list_ref.iter().filter(|&v| v % 2 == 0)) take limit);
+assert_eq!(evens.to_string(), "2 4 ");
+```
+
+To format only every few elements, such as every third tick label on an
+axis, add `step` and the step size after the collection, like
+[`Iterator::step_by`]. The first element is always formatted, followed by
+every step-th element after it. The step size is evaluated once each time
+the value is formatted, and like with `take`, the collection must be a
+single identifier or parenthesized:
+
+```
+use lazy_format::lazy_format;
+
+let ticks = lazy_format!("{t}" sep " " for t in (0..10) step 3);
+assert_eq!(ticks.to_string(), "0 3 6 9");
+
+let labels = ["jan", "feb", "mar", "apr", "may"];
+let every = 2;
+let sparse = lazy_format!("{label}" sep ", " for label in labels step every);
+assert_eq!(sparse.to_string(), "jan, mar, may");
+```
+
+As with [`Iterator::step_by`], formatting panics if the step size is 0.
+
+To skip some of the elements, add `if` and a condition after the
+collection. The condition can refer to the bindings in the loop pattern, as
+well as to any other captured variables. Like with `take`, the collection
+must be a single identifier or parenthesized:
+
+```
+use lazy_format::lazy_format;
+
+let list = vec![1i32, 2, 3, 4, 5];
+let list_ref = &list;
+let threshold = 2;
+
+let large = lazy_format!("{v}" sep ", " for v in list_ref if *v > threshold);
+assert_eq!(large.to_string(), "3, 4, 5");
+```
+
+Skipped elements write nothing at all, not even the separator. This is
+different from an element format that writes an empty string, such as a
+conditional with an empty `else`, which still counts as an element and gets
+a separator:
+
+```
+use lazy_format::lazy_format;
+
+let list = [1, 2, 3, 4];
+
+let skipped = lazy_format!("{v}" sep ", " for v in list if v % 2 == 0);
+assert_eq!(skipped.to_string(), "2, 4");
+
+let empty = lazy_format!(
+    ("{}", lazy_format!(if v % 2 == 0 => "{v}" else => "")) sep ", " for v in list
+);
+assert_eq!(empty.to_string(), ", 2, , 4");
+```
+
+When the name used in the format string doesn't read well as a loop
+binding, add `as` and a new name after the collection, to also bind each
+element under that name. The new name holds the element itself, and the loop
+binding is still available as a reference to it. Like with `take`, the collection
+must be a single identifier or parenthesized:
+
+```
+use lazy_format::lazy_format;
+
+let paths = ["src", "tests"];
+let listing = lazy_format!("Directory: {directory}\n" for p in paths as directory);
+assert_eq!(listing.to_string(), "Directory: src\nDirectory: tests\n");
+
+let names = vec![String::from("a"), String::from("bc")];
+let lengths = lazy_format!(("{name}={}", p.len()) sep ", " for p in (&names) as name);
+assert_eq!(lengths.to_string(), "a=1, bc=2");
+```
+
+To loop over two collections in lockstep, use `zip` and the two
+collections, separated by a comma. Each element is a pair of items, one
+from each collection, and like [`Iterator::zip`], the loop stops at the end
+of the shorter collection:
+
+```
+use lazy_format::lazy_format;
+
+let labels = ["width", "height", "depth"];
+let values = [10, 20];
+
+let pairs = lazy_format!("{label}: {value}" sep ", " for (label, value) in zip labels, values);
+assert_eq!(pairs.to_string(), "width: 10, height: 20");
+```
+
+The collection can be any expression that produces an [`IntoIterator`],
+including generators made with [`iter::from_fn`], which call a closure
+until it returns [`None`]. Because the collection expression is evaluated
+again each time the value is formatted, a generator whose state is created
+inside the expression starts over on each render. A generator that pulls
+from state outside the lazy format (through a [`Cell`] or [`RefCell`], since
+the closure can't mutate its captures) continues from where the last render
+left off, and may be exhausted; use `for ... in collect` to run it only once:
+
+```
+use std::iter;
+use lazy_format::lazy_format;
+
+let limit = 3;
+let countdown = lazy_format!("{n}..." for n in {
+    let mut n = limit + 1;
+    iter::from_fn(move || {
+        n -= 1;
+        (n > 0).then_some(n)
+    })
+});
+
+assert_eq!(countdown.to_string(), "3...2...1...");
+assert_eq!(countdown.to_string(), "3...2...1...");
+```
+
+To loop over a slice (or anything that can be sliced with `[..]`, like an
+array or a [`Vec`]) in fixed-size groups, use `chunks`, the collection, and
+the group size, separated by a comma. Each element is a sub-slice of at most
+that many items, like [`slice::chunks`]; the last one may be shorter. Nest
+another lazy format in the element format to loop over each group:
+
+```
+use lazy_format::lazy_format;
+
+let data: Vec<u8> = (0..20).collect();
+let data = &data;
+
+let dump = lazy_format!(
+    ("{}\n", lazy_format!("{byte:02x}" sep " " for byte in chunk))
+    for chunk in chunks data, 8
+);
+
+assert_eq!(
+    dump.to_string(),
+    "00 01 02 03 04 05 06 07\n\
+     08 09 0a 0b 0c 0d 0e 0f\n\
+     10 11 12 13\n",
+);
+```
+
+As with [`slice::chunks`], formatting panics if the group size is 0.
+
+To loop over the items of a collection of collections, as though they
+were one collection, use `flatten`, like [`Iterator::flatten`]:
+
+```
+use lazy_format::lazy_format;
+
+let groups = vec![vec![1, 2], vec![], vec![3]];
+let groups = &groups;
+
+let all = lazy_format!("{v}" sep ", " for v in flatten groups);
+assert_eq!(all.to_string(), "1, 2, 3");
+```
+
+To loop over a collection from its last item to its first, such as to show
+a history newest-first, use `rev`, like [`Iterator::rev`]. The collection
+doesn't need to be reversed ahead of time, but its iterator must be a
+[`DoubleEndedIterator`]:
+
+```
+use lazy_format::lazy_format;
+
+let history = vec!["open", "edit", "save"];
+let history = &history;
+
+let newest_first = lazy_format!("{action}" sep " <- " for action in rev history);
+assert_eq!(newest_first.to_string(), "save <- edit <- open");
+
+let countdown = lazy_format!("{n}..." for n in rev 1..=3);
+assert_eq!(countdown.to_string(), "3...2...1...");
+```
+
+An iterator that can only go forwards, like one made with
+[`iter::from_fn`], can't be reversed, and is rejected at compile time:
+
+```compile_fail
+use std::iter;
+use lazy_format::lazy_format;
+
+// error: the trait bound `FromFn<...>: DoubleEndedIterator` is not satisfied
+let result = lazy_format!("{n}" for n in rev iter::from_fn(|| Some(1)).take(3));
+```
+
+To number the elements, use `enumerate`; each element is then a pair of
+its index and the item, like with [`Iterator::enumerate`]. Add `from` and a
+starting index to count from something other than 0, such as 1 for
+human-facing lists. The starting index is evaluated once each time the value
+is formatted. When using `from`, the collection must be a single identifier
+or parenthesized:
+
+```
+use lazy_format::lazy_format;
+
+let steps = ["Preheat", "Mix", "Bake"];
+
+let indexed = lazy_format!("{i}:{step}" sep " " for (i, step) in enumerate steps);
+assert_eq!(indexed.to_string(), "0:Preheat 1:Mix 2:Bake");
+
+let numbered = lazy_format!("{n}. {step}\n" for (n, step) in enumerate steps from 1);
+assert_eq!(numbered.to_string(), "1. Preheat\n2. Mix\n3. Bake\n");
+```
+
+To just join values with a separator, without any extra formatting, use
+`sep` and the collection, with no element format. Since this only needs the
+items to be [`Display`], it works well for runtime-determined lists of
+different types, like a slice of `&dyn Display`:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+let name = "Ferris";
+let greeting = lazy_format!("hello {name}");
+let items: &[&dyn Display] = &[&1, &"two", &greeting];
+
+let joined = lazy_format!(sep ", " in items);
+assert_eq!(joined.to_string(), "1, two, hello Ferris");
+```
+
+For more complex lazy string joining, check out [joinery](/joinery).
+
+If the collection contains [`Result`]s, use `for ... in try` to format the
+[`Ok`] values. Formatting stops with an error at the first [`Err`]. Because
+[`fmt::Error`] is the only error that a [`Display`] implementation can return,
+the error value itself is discarded.
+
+```
+use std::fmt::Write;
+use lazy_format::lazy_format;
+
+let good: [Result<i32, &str>; 3] = [Ok(1), Ok(2), Ok(3)];
+let result = lazy_format!("{v} " for v in try good);
+assert_eq!(result.to_string(), "1 2 3 ");
+
+let bad: [Result<i32, &str>; 3] = [Ok(1), Err("bad"), Ok(3)];
+let result = lazy_format!("{v} " for v in try bad);
+
+let mut dest = String::new();
+assert!(write!(&mut dest, "{}", result).is_err());
+assert_eq!(dest, "1 ");
+```
+
+Because the collection expression is evaluated each time the value is
+formatted, the loop never silently renders nothing the second time around. A
+one-shot iterator (one that isn't [`Copy`], like most iterator adapters over
+owned data) that was created outside the lazy format can't be looped over
+directly, because it would be consumed by the first render; this is
+rejected at compile time:
+
+```compile_fail
+use lazy_format::lazy_format;
+
+let words = vec!["a".to_owned(), "bcd".to_owned()];
+let lengths = words.into_iter().map(|w| w.len());
+
+// error: cannot move out of `lengths`, a captured variable in an `Fn` closure
+let result = lazy_format!("{len} " for len in lengths);
+```
+
+Iterators created inside the collection expression, like
+`list.iter().map(...)`, are fine, since they're created fresh for each
+render. Otherwise, with the `alloc` feature, use `for ... in collect` to
+instead collect the iterator into a [`Vec`] once, when the lazy format is
+created. The formatting itself is
+still deferred, and each render loops over the same collected items, so any
+side effects of the iterator happen exactly once. The loop pattern binds
+references to the collected items. This trades an allocation for the ability
+to render the value any number of times:
+
+```
+use std::fmt::Display;
+use lazy_format::lazy_format;
+
+fn lengths(words: Vec<String>) -> impl Display {
+    lazy_format!("{len}" sep ", " for len in collect words.into_iter().map(|w| w.len()))
+}
+
+let result = lengths(vec!["a".into(), "bcd".into(), "ef".into()]);
+assert_eq!(result.to_string(), "1, 3, 2");
+assert_eq!(result.to_string(), "1, 3, 2");
+```
+
+A whole loop, in parentheses, can be used anywhere a format can, such as in
+an arm of a conditional format, or as the element format of another loop.
+The loop works just like a nested `lazy_format!`: in particular, its
+collection is captured from the enclosing format, so a borrowed collection
+should be a reference (or some other [`Copy`] value) rather than something
+like `&items`:
+
+```
+use lazy_format::lazy_format;
+
+let items = vec![1, 2, 3];
+let items = &items;
+
+let listing = |verbose: bool| lazy_format!(
+    if verbose => ("{v}\n" for v in items)
+    else => ("({} items)", items.len())
+);
+
+assert_eq!(listing(true).to_string(), "1\n2\n3\n");
+assert_eq!(listing(false).to_string(), "(3 items)");
+
+let grid = [[1, 2], [3, 4]];
+let rows = lazy_format!(("{cell}" sep " " for cell in row) sep "\n" for row in grid);
+assert_eq!(rows.to_string(), "1 2\n3 4");
+```
+
+[`Copy`]: https://doc.rust-lang.org/std/marker/trait.Copy.html
+[`Iterator::zip`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.zip
+[`Iterator::rev`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.rev
+[`Iterator::step_by`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.step_by
+[`DoubleEndedIterator`]: https://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html
+[`slice::chunks`]: https://doc.rust-lang.org/std/primitive.slice.html#method.chunks
+[`Iterator::flatten`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.flatten
+[`IntoIterator`]: https://doc.rust-lang.org/std/iter/trait.IntoIterator.html
+[`iter::from_fn`]: https://doc.rust-lang.org/std/iter/fn.from_fn.html
+[`Cell`]: https://doc.rust-lang.org/std/cell/struct.Cell.html
+[`RefCell`]: https://doc.rust-lang.org/std/cell/struct.RefCell.html
+[`Iterator::enumerate`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.enumerate
+[`Vec`]: https://doc.rust-lang.org/std/vec/struct.Vec.html
+[`HashMap`]: https://doc.rust-lang.org/std/collections/struct.HashMap.html
+[`BTreeMap`]: https://doc.rust-lang.org/std/collections/struct.BTreeMap.html
+[`Result`]: https://doc.rust-lang.org/std/result/enum.Result.html
+[`Ok`]: https://doc.rust-lang.org/std/result/enum.Result.html#variant.Ok
+[`Err`]: https://doc.rust-lang.org/std/result/enum.Result.html#variant.Err
+[`fmt::Error`]: https://doc.rust-lang.org/std/fmt/struct.Error.html
+
+For simple repetition, where there's no collection to loop over, use
+`repeat`. The count expression is evaluated each time the value is formatted:
+
+```
+use lazy_format::lazy_format;
+
+let width = 5;
+let rule = lazy_format!("=" repeat width);
+assert_eq!(rule.to_string(), "=====");
+
+let fill = '-';
+let dashes = lazy_format!(("{fill}{fill}") repeat 3);
+assert_eq!(dashes.to_string(), "------");
+```
+
+[`format!`]: https://doc.rust-lang.org/std/macro.format.html
+[`format_args!`]: https://doc.rust-lang.org/std/macro.format_args.html
+[`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+[`Formatter`]: https://doc.rust-lang.org/std/fmt/struct.Formatter.html
+[`Debug`]: https://doc.rust-lang.org/std/fmt/trait.Debug.html
+[`dbg!`]: https://doc.rust-lang.org/std/macro.dbg.html
+[`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+[`File`]: https://doc.rust-lang.org/std/fs/struct.File.html
+*/
+#[macro_export]
+macro_rules! lazy_format {
+    // Bound lazy format: evaluate each `$value` once per format, and make it
+    // available as `$binding` to the rest of the lazy format. The bindings
+    // are collected first, and then written in the same closure as the body
+    // of the rest of the lazy format, so that the body can use the other
+    // captures of the closure by reference.
+    (@once [$($bindings:tt)*] once $binding:pat = $value:expr; $($rest:tt)+) => {
+        $crate::lazy_format!(@once [$($bindings)* let $binding = $value;] $($rest)+)
+    };
+
+    (@once [$($bindings:tt)*] $($rest:tt)+) => {
+        $crate::make_lazy_format!(|f| {
+            $($bindings)*
+            $crate::lazy_format!(@body f $($rest)+)
+        })
+    };
+
+    (once $($rest:tt)+) => {
+        $crate::lazy_format!(@once [] once $($rest)+)
+    };
+
+    // Conditional lazy format. The arms are parsed a few at a time, so that
+    // a trailing `default` arm can be rewritten to `_`, and guards which
+    // can't be parsed as an `expr` (such as `if let` guards) are forwarded to
+    // the `match` unchanged. `$fallback` is an extra arm to add at the end of
+    // the match.
+    (@match $f:ident ($condition:expr) [$($fallback:tt)*] [$($arms:tt)*] $(,)?) => {
+        match $condition {
+            $($arms)*
+            $($fallback)*
+        }
+    };
+
+    // Runs of plain arms are taken eight at a time, so that long matches
+    // don't run into the recursion limit. At least one more arm has to
+    // follow, so `default` is only ever a keyword in the last arm; anywhere
+    // else it's an ordinary binding.
+    (
+        @match $f:ident ($condition:expr) [$($fallback:tt)*] [$($arms:tt)*]
+        $p1:pat $(if $g1:expr)? => $o1:tt,
+        $p2:pat $(if $g2:expr)? => $o2:tt,
+        $p3:pat $(if $g3:expr)? => $o3:tt,
+        $p4:pat $(if $g4:expr)? => $o4:tt,
+        $p5:pat $(if $g5:expr)? => $o5:tt,
+        $p6:pat $(if $g6:expr)? => $o6:tt,
+        $p7:pat $(if $g7:expr)? => $o7:tt,
+        $p8:pat $(if $g8:expr)? => $o8:tt,
+        $($rest:tt)+
+    ) => {
+        $crate::lazy_format!(
+            @match $f ($condition) [$($fallback)*]
+            [
+                $($arms)*
+                $p1 $(if $g1)? => $crate::write_tt!($f, $o1),
+                $p2 $(if $g2)? => $crate::write_tt!($f, $o2),
+                $p3 $(if $g3)? => $crate::write_tt!($f, $o3),
+                $p4 $(if $g4)? => $crate::write_tt!($f, $o4),
+                $p5 $(if $g5)? => $crate::write_tt!($f, $o5),
+                $p6 $(if $g6)? => $crate::write_tt!($f, $o6),
+                $p7 $(if $g7)? => $crate::write_tt!($f, $o7),
+                $p8 $(if $g8)? => $crate::write_tt!($f, $o8),
+            ]
+            $($rest)+
+        )
+    };
+
+    // `@` arms format any `Display` value, with the same formatter
+    (@match $f:ident ($condition:expr) [$($fallback:tt)*] [$($arms:tt)*] default => @ $value:expr $(,)?) => {
+        $crate::lazy_format!(
+            @match $f ($condition) [$($fallback)*]
+            [$($arms)* _ => ::core::fmt::Display::fmt(&$value, $f),]
+        )
+    };
+
+    (@match $f:ident ($condition:expr) [$($fallback:tt)*] [$($arms:tt)*] $pattern:pat $(if $guard:expr)? => @ $value:expr $(, $($rest:tt)*)?) => {
+        $crate::lazy_format!(
+            @match $f ($condition) [$($fallback)*]
+            [$($arms)* $pattern $(if $guard)? => ::core::fmt::Display::fmt(&$value, $f),]
+            $($($rest)*)?
+        )
+    };
+
+    (@match $f:ident ($condition:expr) [$($fallback:tt)*] [$($arms:tt)*] default => $output:tt $(,)?) => {
+        $crate::lazy_format!(
+            @match $f ($condition) [$($fallback)*]
+            [$($arms)* _ => $crate::write_tt!($f, $output),]
+        )
+    };
+
+    (@match $f:ident ($condition:expr) [$($fallback:tt)*] [$($arms:tt)*] $pattern:pat $(if $guard:expr)? => $output:tt $(, $($rest:tt)*)?) => {
+        $crate::lazy_format!(
+            @match $f ($condition) [$($fallback)*]
+            [$($arms)* $pattern $(if $guard)? => $crate::write_tt!($f, $output),]
+            $($($rest)*)?
+        )
+    };
+
+    // Guards that start with `let` can't be parsed as an `expr`, so they're
+    // collected token by token up to their `=>` instead. A `let` later in
+    // the chain, after an ordinary condition, isn't supported.
+    (@match $f:ident ($condition:expr) [$($fallback:tt)*] [$($arms:tt)*] $pattern:pat if let $($rest:tt)+) => {
+        $crate::lazy_format!(@guard $f ($condition) [$($fallback)*] [$($arms)*] [$pattern] [let] $($rest)+)
+    };
+
+    (@guard $f:ident ($condition:expr) [$($fallback:tt)*] [$($arms:tt)*] [$pattern:pat] [$($guard:tt)+] => @ $value:expr $(, $($rest:tt)*)?) => {
+        $crate::lazy_format!(
+            @match $f ($condition) [$($fallback)*]
+            [$($arms)* $pattern if $($guard)+ => ::core::fmt::Display::fmt(&$value, $f),]
+            $($($rest)*)?
+        )
+    };
+
+    (@guard $f:ident ($condition:expr) [$($fallback:tt)*] [$($arms:tt)*] [$pattern:pat] [$($guard:tt)+] => $output:tt $(, $($rest:tt)*)?) => {
+        $crate::lazy_format!(
+            @match $f ($condition) [$($fallback)*]
+            [$($arms)* $pattern if $($guard)+ => $crate::write_tt!($f, $output),]
+            $($($rest)*)?
+        )
+    };
+
+    (@guard $f:ident ($condition:expr) [$($fallback:tt)*] [$($arms:tt)*] [$pattern:pat] [$($guard:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::lazy_format!(@guard $f ($condition) [$($fallback)*] [$($arms)*] [$pattern] [$($guard)* $next] $($rest)*)
+    };
+
+    // Literal lazy format: if `$pattern` doesn't have any placeholders, it's
+    // a `LazyStr`. Otherwise it may still implicitly capture named arguments,
+    // so it's a regular lazy format. The choice is made at compile time, by
+    // selecting on the const analysis of the pattern.
+    ($pattern:literal $(,)?) => {{
+        const LITERAL: ::core::option::Option<&str> = $crate::literal_str!($pattern);
+
+        struct Select<const IS_LITERAL: bool>;
+
+        #[allow(dead_code)]
+        impl Select<true> {
+            #[inline(always)]
+            fn select<T>(_lazy: T) -> $crate::LazyStr {
+                $crate::LazyStr::new(match LITERAL {
+                    ::core::option::Option::Some(literal) => literal,
+                    ::core::option::Option::None => "",
+                })
+            }
+        }
+
+        #[allow(dead_code)]
+        impl Select<false> {
+            #[inline(always)]
+            fn select<T>(lazy: T) -> T {
+                lazy
+            }
+        }
+
+        Select::<{ LITERAL.is_some() }>::select($crate::make_lazy_format!(|f| {
+            $crate::write!(f, $pattern)
+        }))
+    }};
+
+    // A single value: like a string, the output is truncated to the
+    // precision of the destination formatter, if any.
+    ("{}", $($args:tt)+) => {
+        $crate::lazy_format!(@args [precise] "{}" [] [] $($args)+)
+    };
+
+    // Basic lazy format: collect $args and format via `$pattern` when writing
+    // to a destination.
+    ($pattern:literal, $($args:tt)+) => {
+        $crate::lazy_format!(@args [plain] $pattern [] [] $($args)+)
+    };
+
+    // The arguments are parsed one at a time, so that arguments of the form
+    // `ref name` or `ref name.field` can be borrowed before the closure,
+    // rather than moved into it. `ref` can't start an expression, so these
+    // rules never change the meaning of an ordinary argument, like `&name`.
+    // Each `borrowed` is a distinct variable, thanks to macro hygiene. Fields
+    // are `tt`s, to allow tuple fields like `.0`. The mode, `[precise]` or
+    // `[plain]`, says whether the output is truncated to the precision.
+    (@args [precise] $pattern:literal [$($borrows:tt)*] [$($args:tt)*] $(,)?) => {{
+        $($borrows)*
+        $crate::make_lazy_format!(|f| {
+            $crate::write_precise(f, |f| $crate::write!(f, $pattern, $($args)*))
+        })
+    }};
+
+    (@args [plain] $pattern:literal [$($borrows:tt)*] [$($args:tt)*] $(,)?) => {{
+        $($borrows)*
+        $crate::make_lazy_format!(|f| $crate::write!(f, $pattern, $($args)*))
+    }};
+
+    (@args $mode:tt $pattern:literal [$($borrows:tt)*] [$($args:tt)*] ref $name:ident $(. $field:tt)* $(, $($rest:tt)*)?) => {
+        $crate::lazy_format!(
+            @args $mode $pattern [$($borrows)* let borrowed = &$name $(. $field)*;] [$($args)* borrowed,]
+            $($($rest)*)?
+        )
+    };
+
+    (@args $mode:tt $pattern:literal [$($borrows:tt)*] [$($args:tt)*] $key:ident = ref $name:ident $(. $field:tt)* $(, $($rest:tt)*)?) => {
+        $crate::lazy_format!(
+            @args $mode $pattern [$($borrows)* let borrowed = &$name $(. $field)*;] [$($args)* $key = borrowed,]
+            $($($rest)*)?
+        )
+    };
+
+    (@args $mode:tt $pattern:literal [$($borrows:tt)*] [$($args:tt)*] $key:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::lazy_format!(@args $mode $pattern [$($borrows)*] [$($args)* $key = $value,] $($($rest)*)?)
+    };
+
+    (@args $mode:tt $pattern:literal [$($borrows:tt)*] [$($args:tt)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::lazy_format!(@args $mode $pattern [$($borrows)*] [$($args)* $value,] $($($rest)*)?)
+    };
+
+    // Collected looping formatter: collect `$collection` into a `Vec` right
+    // away, and loop over references to its items each time the value is
+    // formatted.
+    (
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:pat in collect $collection:expr
+    ) => {{
+        let collected = $crate::collect_vec($collection);
+        $crate::make_lazy_format!(|f| $crate::lazy_format!(
+            @loop f [$($prefix)?] [$($suffix)?] [$($separator)?] [] []
+            $output for $item in &collected
+        ))
+    }};
+
+    // Every other form is written by an `@body` rule, which writes the
+    // output to the formatter `$f`. This allows `once` to put its bindings
+    // in the same closure as the body.
+    (@body $f:ident "{}", $($args:tt)+) => {
+        $crate::write_precise($f, |f| $crate::write!(f, "{}", $($args)+))
+    };
+
+    (@body $f:ident $pattern:literal $(, $($args:tt)*)?) => {
+        $crate::write!($f, $pattern $(, $($args)*)?)
+    };
+
+    // Conditional lazy format: evaluate a match expression and format based on
+    // the matching arm. Several comma separated conditions are matched as a
+    // tuple.
+    (@body $f:ident match ($($condition:expr),+) { $($arms:tt)* }) => {
+        $crate::lazy_format!(@match $f (($($condition),+)) [] [] $($arms)*)
+    };
+
+    // Conditional lazy format with an implicit catch-all arm that writes
+    // nothing
+    (@body $f:ident match? ($($condition:expr),+) { $($arms:tt)* }) => {
+        $crate::lazy_format!(
+            @match $f (($($condition),+))
+            [#[allow(unreachable_patterns)] _ => ::core::fmt::Result::Ok(()),]
+            []
+            $($arms)*
+        )
+    };
+
+    // Optional lazy format: format the value in an `Option`, or a fallback
+    // if there isn't one
+    (@body $f:ident option ($option:expr) as $binding:pat => $some_output:tt else $none_output:tt) => {
+        match $option {
+            ::core::option::Option::Some($binding) => $crate::write_tt!($f, $some_output),
+            ::core::option::Option::None => $crate::write_tt!($f, $none_output),
+        }
+    };
+
+    (@body $f:ident option $option:expr => $some_output:tt else $none_output:tt) => {
+        match $option {
+            ::core::option::Option::Some(value) => $crate::write_tt!($f, $some_output, value),
+            ::core::option::Option::None => $crate::write_tt!($f, $none_output),
+        }
+    };
+
+    // Result lazy format: format the value in an `Ok` or the error in an
+    // `Err`, in the same style as the `option` form
+    (@body $f:ident result ($result:expr) as $ok_binding:pat => $ok_output:tt else $err_binding:pat => $err_output:tt) => {
+        match $result {
+            ::core::result::Result::Ok($ok_binding) => $crate::write_tt!($f, $ok_output),
+            ::core::result::Result::Err($err_binding) => $crate::write_tt!($f, $err_output),
+        }
+    };
+
+    (@body $f:ident result $result:expr => $ok_output:tt else $err_output:tt) => {
+        match $result {
+            ::core::result::Result::Ok(value) => $crate::write_tt!($f, $ok_output, value),
+            ::core::result::Result::Err(err) => $crate::write_tt!($f, $err_output, err),
+        }
+    };
+
+    // Boolean lazy format: write one of two outputs, depending on a flag, or
+    // one of several, with `else show`. The branches are collected one at a
+    // time into an `if` / `else if` / `else` chain.
+    (@body $f:ident show $($rest:tt)+) => {
+        $crate::lazy_format!(@show $f [] $($rest)+)
+    };
+
+    (@show $f:ident [$($chain:tt)*] $condition:expr => $output:tt else show $($rest:tt)+) => {
+        $crate::lazy_format!(
+            @show $f [$($chain)* if $condition { $crate::write_tt!($f, $output) } else]
+            $($rest)+
+        )
+    };
+
+    (@show $f:ident [$($chain:tt)*] $condition:expr => $output:tt else $off_output:tt) => {
+        $($chain)* if $condition {
+            $crate::write_tt!($f, $output)
+        } else {
+            $crate::write_tt!($f, $off_output)
+        }
+    };
+
+
+    // Conditional lazy format: evaluate an if / else if / else expression and
+    // format based on the successful branch
+    (
+        @body $f:ident
+        if $(let $match:pat = )? $condition:expr => $output:tt
+        $(else if $(let $elseif_match:pat = )? $elseif_condition:expr => $elseif_output:tt)*
+        $(else $(let $else_match:pat = $else_value:expr)? => $else_output:tt)?
+    ) => {
+        if $(let $match = )? $condition {
+            $crate::write_tt!($f, $output)
+        }
+        $(else if $(let $elseif_match = )? $elseif_condition {
+            $crate::write_tt!($f, $elseif_output)
+        })*
+        $(else if true {
+            $(let $else_match = $else_value;)?
+            $crate::write_tt!($f, $else_output)
+        })?
+        else {
+            ::core::fmt::Result::Ok(())
+        }
+    };
+
+    // Padded formatter: write `$value`, followed by `$fill` up to `$width`
+    // chars
+    (@body $f:ident pad $value:expr, $width:expr, $fill:expr $(,)?) => {
+        ::core::fmt::Display::fmt(
+            &$crate::transform::column(&$value, $width, $crate::transform::Align::Left)
+                .with_fill($fill),
+            $f,
+        )
+    };
+
+    // Joining formatter: format each item in `$items` as-is, with a
+    // separator between them
+    (@body $f:ident sep $separator:tt in $items:expr) => {
+        $crate::lazy_format!(@body $f "{item}" sep $separator for item in $items)
+    };
+
+    // Repeating formatter: format `$output` `$count` times
+    (@body $f:ident $output:tt repeat $count:expr) => {{
+        let count: usize = $count;
+        ::core::iter::Iterator::try_for_each(&mut (0..count), |_| $crate::write_tt!($f, $output))
+    }};
+
+    // Fallible looping formatter: format each `Ok($item)` in `$collection`,
+    // stopping with an error at the first `Err`. Needs to come before the
+    // regular looping formatter, since `try` can't be parsed as an expression.
+    (@body $f:ident $output:tt for $item:pat in try $collection:expr) => {{
+        let mut iter = ::core::iter::IntoIterator::into_iter($collection);
+        ::core::iter::Iterator::try_for_each(&mut iter, |item| match item {
+            ::core::result::Result::Ok($item) => $crate::write_tt!($f, $output),
+            ::core::result::Result::Err(_) => ::core::result::Result::Err(::core::fmt::Error),
+        })
+    }};
+
+    // Limited looping formatter: like the regular looping formatter, but
+    // formats at most `$limit` items, and writes `$overflow` if there were
+    // more. `$collection` has to be a single token tree here, since an `expr`
+    // can't be followed by `take`.
+    (
+        @body $f:ident
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:pat in $collection:tt take $limit:tt $(else $overflow:tt)?
+    ) => {
+        $crate::lazy_format!(
+            @loop $f [$($prefix)?] [$($suffix)?] [$($separator)?] [$limit $($overflow)?] []
+            $output for $item in $collection
+        )
+    };
+
+    // Stepped looping formatter: loop over every `$step`th item of
+    // `$collection`, starting with the first. As with `take`, `$collection`
+    // has to be a single token tree.
+    (
+        @body $f:ident
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:pat in $collection:tt step $step:expr
+    ) => {
+        $crate::lazy_format!(
+            @step $f [$($prefix)?] [$($suffix)?] [$($separator)?] [$step]
+            $output for $item in $collection
+        )
+    };
 
-## `match` conditional example:
+    (@step $f:ident
+        [$($prefix:tt)?] [$($suffix:tt)?] [$($separator:tt)?] [$step:expr]
+        $output:tt for $item:pat in $collection:expr
+    ) => {
+        $crate::lazy_format!(
+            @loop $f [$($prefix)?] [$($suffix)?] [$($separator)?] [] []
+            $output for $item in ::core::iter::Iterator::step_by(
+                ::core::iter::IntoIterator::into_iter($collection),
+                $step,
+            )
+        )
+    };
 
-```
-use std::fmt::Display;
-use lazy_format::lazy_format;
+    // Zipped looping formatter: loop over the pairs of items from two
+    // collections, stopping at the end of the shorter one
+    (
+        @body $f:ident
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:pat in zip $left:expr, $right:expr
+    ) => {
+        $crate::lazy_format!(
+            @loop $f [$($prefix)?] [$($suffix)?] [$($separator)?] [] []
+            $output for $item in ::core::iter::Iterator::zip(
+                ::core::iter::IntoIterator::into_iter($left),
+                $right,
+            )
+        )
+    };
 
-fn get_number(num: usize) -> impl Display {
-    // Note that the parenthesis in the match conditional are required,
-    // due to limitations in Rust's macro parsing (can't follow an
-    // expression with `{}`)
-    lazy_format!(match (num) {
-        0 => "Zero",
-        1 => "One",
-        2 => "Two",
-        3 => "Three",
-        4 | 5 => "Four or five",
-        value if value % 2 == 0 => ("A large even number: {}", value),
-        value => "An unrecognized number: {value}",
-    })
+    // Chunked looping formatter: loop over the sub-slices of `$collection`,
+    // `$size` elements at a time
+    (
+        @body $f:ident
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:pat in chunks $collection:expr, $size:expr
+    ) => {
+        $crate::lazy_format!(
+            @loop $f [$($prefix)?] [$($suffix)?] [$($separator)?] [] []
+            $output for $item in <[_]>::chunks(&$collection[..], $size)
+        )
+    };
+
+    // Reversed looping formatter: loop over the items of `$collection` from
+    // last to first
+    (
+        @body $f:ident
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:pat in rev $collection:expr
+    ) => {
+        $crate::lazy_format!(
+            @loop $f [$($prefix)?] [$($suffix)?] [$($separator)?] [] []
+            $output for $item in $crate::reversed($collection)
+        )
+    };
+
+    // Flattened looping formatter: loop over the items of each of the
+    // collections in `$collection`, in order
+    (
+        @body $f:ident
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:pat in flatten $collection:expr
+    ) => {
+        $crate::lazy_format!(
+            @loop $f [$($prefix)?] [$($suffix)?] [$($separator)?] [] []
+            $output for $item in ::core::iter::Iterator::flatten(
+                ::core::iter::IntoIterator::into_iter($collection)
+            )
+        )
+    };
+
+    // Enumerated looping formatter: loop over the items of a collection
+    // paired with their indexes, starting from `$start`. `$collection` has to
+    // be a single token tree, since an `expr` can't be followed by `from`.
+    (
+        @body $f:ident
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:pat in enumerate $collection:tt from $start:expr
+    ) => {
+        $crate::lazy_format!(
+            @enumerate $f [$($prefix)?] [$($suffix)?] [$($separator)?] [$start]
+            $output for $item in $collection
+        )
+    };
+
+    (
+        @body $f:ident
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:pat in enumerate $collection:expr
+    ) => {
+        $crate::lazy_format!(
+            @enumerate $f [$($prefix)?] [$($suffix)?] [$($separator)?] []
+            $output for $item in $collection
+        )
+    };
+
+    (@enumerate $f:ident
+        [$($prefix:tt)?] [$($suffix:tt)?] [$($separator:tt)?] []
+        $output:tt for $item:pat in $collection:expr
+    ) => {
+        $crate::lazy_format!(
+            @loop $f [$($prefix)?] [$($suffix)?] [$($separator)?] [] []
+            $output for $item in ::core::iter::Iterator::enumerate(
+                ::core::iter::IntoIterator::into_iter($collection)
+            )
+        )
+    };
+
+    (@enumerate $f:ident
+        [$($prefix:tt)?] [$($suffix:tt)?] [$($separator:tt)?] [$start:expr]
+        $output:tt for $item:pat in $collection:expr
+    ) => {
+        $crate::lazy_format!(
+            @loop $f [$($prefix)?] [$($suffix)?] [$($separator)?] [] []
+            $output for $item in ::core::iter::Iterator::map(
+                ::core::iter::Iterator::enumerate(::core::iter::IntoIterator::into_iter($collection)),
+                {
+                    let start: usize = $start;
+                    move |(index, item)| (start + index, item)
+                },
+            )
+        )
+    };
+
+    // Renamed looping formatter: bind each item as `$name`, and `$item` as a
+    // reference to it. `$collection` has to be a single token tree, since
+    // otherwise `as` would be parsed as part of a cast expression.
+    (
+        @body $f:ident
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:ident in $collection:tt as $name:ident
+    ) => {
+        $crate::lazy_format!(
+            @loop $f [$($prefix)?] [$($suffix)?] [$($separator)?] [] []
+            $output as [$item $name] for $item in $collection
+        )
+    };
+
+    // Filtered looping formatter: like the regular looping formatter, but
+    // skips items for which `$guard` is false. As with `take`, `$collection`
+    // has to be a single token tree.
+    (
+        @body $f:ident
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:pat in $collection:tt if $guard:expr
+    ) => {
+        $crate::lazy_format!(
+            @loop $f [$($prefix)?] [$($suffix)?] [$($separator)?] [] [$guard]
+            $output for $item in $collection
+        )
+    };
+
+    // Looping formatter: format each `$item` in `$collection` with the format
+    // arguments, optionally with a separator between each item, and a prefix
+    // and suffix around the whole thing
+    (
+        @body $f:ident
+        $output:tt
+        $(wrap $prefix:tt $suffix:tt)?
+        $(sep $separator:tt)?
+        for $item:pat in $collection:expr
+    ) => {
+        $crate::lazy_format!(
+            @loop $f [$($prefix)?] [$($suffix)?] [$($separator)?] [] []
+            $output for $item in $collection
+        )
+    };
+
+    // The limit is applied by taking from a `by_ref` of the iterator, so that
+    // afterwards we can check if it had any more elements.
+    (@loop $f:ident
+        [$($prefix:tt)?] [$($suffix:tt)?] [] [$($limit:tt $($overflow:tt)?)?] [$($guard:expr)?]
+        $output:tt $(as [$binding:ident $name:ident])? for $item:pat in $collection:expr
+    ) => {{
+        $($crate::write_tt!($f, $prefix)?;)?
+        let iter = ::core::iter::IntoIterator::into_iter($collection);
+        $(
+            let limit: usize = $limit;
+            let mut rest = iter;
+            let iter = ::core::iter::Iterator::take(::core::iter::Iterator::by_ref(&mut rest), limit);
+        )?
+        let mut iter = iter;
+        ::core::iter::Iterator::try_for_each(&mut iter, |$item| {
+            $(
+                let $name = $binding;
+                #[allow(unused_variables)]
+                let $binding = &$name;
+            )?
+            $(if !($guard) {
+                return ::core::fmt::Result::Ok(());
+            })?
+            $crate::write_tt!($f, $output)
+        })?;
+        $($(
+            if ::core::iter::Iterator::next(&mut rest).is_some() {
+                $crate::write_tt!($f, $overflow)?;
+            }
+        )?)?
+        $($crate::write_tt!($f, $suffix)?;)?
+        ::core::fmt::Result::Ok(())
+    }};
+
+    (@loop $f:ident
+        [$($prefix:tt)?] [$($suffix:tt)?] [$separator:tt] [$($limit:tt $($overflow:tt)?)?] [$($guard:expr)?]
+        $output:tt $(as [$binding:ident $name:ident])? for $item:pat in $collection:expr
+    ) => {{
+        $($crate::write_tt!($f, $prefix)?;)?
+        let iter = ::core::iter::IntoIterator::into_iter($collection);
+        $(
+            let limit: usize = $limit;
+            let mut rest = iter;
+            let iter = ::core::iter::Iterator::take(::core::iter::Iterator::by_ref(&mut rest), limit);
+        )?
+        let mut iter = iter;
+        let mut first = true;
+        ::core::iter::Iterator::try_for_each(&mut iter, |$item| {
+            $(
+                let $name = $binding;
+                #[allow(unused_variables)]
+                let $binding = &$name;
+            )?
+            $(if !($guard) {
+                return ::core::fmt::Result::Ok(());
+            })?
+            if !::core::mem::replace(&mut first, false) {
+                $crate::write_tt!($f, $separator)?;
+            }
+            $crate::write_tt!($f, $output)
+        })?;
+        $($(
+            if ::core::iter::Iterator::next(&mut rest).is_some() {
+                $crate::write_tt!($f, $overflow)?;
+            }
+        )?)?
+        $($crate::write_tt!($f, $suffix)?;)?
+        ::core::fmt::Result::Ok(())
+    }};
+
+    ($($form:tt)+) => {
+        $crate::make_lazy_format!(|f| $crate::lazy_format!(@body f $($form)+))
+    };
 }
 
-assert_eq!(get_number(0).to_string(), "Zero");
-assert_eq!(get_number(1).to_string(), "One");
-assert_eq!(get_number(2).to_string(), "Two");
-assert_eq!(get_number(3).to_string(), "Three");
-assert_eq!(get_number(4).to_string(), "Four or five");
-assert_eq!(get_number(5).to_string(), "Four or five");
-assert_eq!(get_number(6).to_string(), "A large even number: 6");
-assert_eq!(get_number(7).to_string(), "An unrecognized number: 7");
-```
+/**
+Short alias for [`lazy_format!`], for code that uses it heavily. It supports
+exactly the same syntax; see [`lazy_format!`] for details. [`lazy_format!`]
+remains the canonical name.
 
-## `if` conditional example:
+# Example
 
 ```
-use std::fmt::Display;
-use lazy_format::lazy_format;
+use lazy_format::prelude::*;
 
-fn describe_number(value: isize) -> impl Display {
-    lazy_format!(
-        if value < 0 => ("A negative number: {}", value)
-        else if value % 3 == 0 => ("A number divisible by 3: {}", value)
-        else if value % 2 == 1 => ("An odd number: {}", value)
-        else => "Some other kind of number"
-    )
+let name = "World";
+let result = lazy!("Hello, {name}!");
+assert_eq!(result.to_string(), "Hello, World!");
+```
+*/
+#[macro_export]
+macro_rules! lazy {
+    ($($tt:tt)*) => {
+        $crate::lazy_format!($($tt)*)
+    };
 }
 
-assert_eq!(describe_number(-2).to_string(), "A negative number: -2");
-assert_eq!(describe_number(-1).to_string(), "A negative number: -1");
-assert_eq!(describe_number(0).to_string(), "A number divisible by 3: 0");
-assert_eq!(describe_number(1).to_string(), "An odd number: 1");
-assert_eq!(describe_number(2).to_string(), "Some other kind of number");
-assert_eq!(describe_number(3).to_string(), "A number divisible by 3: 3");
-```
+/**
+An owned, storable alternative to [`format_args!`]. It takes exactly the same
+arguments as [`format_args!`], and produces a value that can be formatted
+the same way, but which can be stored, returned from functions, and used
+after the end of the statement that created it.
 
-`if` formatters are allowed to exclude the final `else` branch, in which
-case `lazy_format` will simply write an empty string:
+It differs from [`format_args!`] in a few important ways:
+
+- [`format_args!`] evaluates its arguments immediately and borrows them, so
+  the [`fmt::Arguments`] it creates can't outlive the statement it's created
+  in. `lazy_args!` instead captures the variables used in its arguments (by
+  move, like a `move` closure), and doesn't evaluate the arguments at all
+  until it's formatted.
+- Because the arguments are evaluated each time the value is formatted, any
+  side effects in them happen each time, too.
+- There's no equivalent of [`fmt::Arguments::as_str`]; see
+  [`LazyStr`][crate::LazyStr] for plain strings.
+
+`lazy_args!` is the same as the basic form of [`lazy_format!`]; it exists
+to make this relationship explicit.
+
+# Example
 
 ```
 use std::fmt::Display;
-use lazy_format::lazy_format;
+use lazy_format::lazy_args;
 
-fn only_evens(value: i32) -> impl Display {
-    lazy_format!(if value % 2 == 0 => ("An even number: {}", value))
+fn make_args(name: String, count: usize) -> impl Display {
+    // This couldn't be done with format_args!, which would borrow `name`
+    lazy_args!("{name} has {} items", count)
 }
 
-assert_eq!(only_evens(10).to_string(), "An even number: 10");
-assert_eq!(only_evens(5).to_string(), "");
+let stored = make_args("list".to_string(), 3);
+assert_eq!(stored.to_string(), "list has 3 items");
 ```
 
-## `if let` conditional example:
+[`format_args!`]: https://doc.rust-lang.org/std/macro.format_args.html
+[`fmt::Arguments`]: https://doc.rust-lang.org/std/fmt/struct.Arguments.html
+[`fmt::Arguments::as_str`]: https://doc.rust-lang.org/std/fmt/struct.Arguments.html#method.as_str
+*/
+#[macro_export]
+macro_rules! lazy_args {
+    ($pattern:literal $(, $($args:tt)*)?) => {
+        $crate::lazy_format!($pattern $(, $($args)*)?)
+    };
+}
+
+/**
+Lazily concatenate several [`Display`] values. Each of the arguments is
+written in order, without any separators. As with [`lazy_format!`], the
+argument expressions are captured, rather than evaluated, and are evaluated
+each time the value is formatted.
+
+This is equivalent to `lazy_format!("{}{}{}", a, b, c)`, but is often easier
+to read, especially when there are many arguments.
+
+# Example
 
 ```
 use std::fmt::Display;
-use lazy_format::lazy_format;
+use lazy_format::{lazy_concat, lazy_format};
 
-fn describe_optional_number(value: Option<isize>) -> impl Display {
-    lazy_format!(
-        if let Some(10) = value => "It's ten!"
-        else if let Some(3) | Some(4) = value => "It's three or four!"
-        else if let | Some(0) = value => "It's zero!"
-        else if let Some(x) = value => ("It's some other value: {}", x)
-        else => "It's not a number!"
-    )
+fn greeting<'a>(name: &'a str) -> impl Display + 'a {
+    let salutation = lazy_format!("Hello, {name}");
+    lazy_concat!("<", salutation, if name.is_empty() { "" } else { "!" }, ">")
 }
 
-assert_eq!(describe_optional_number(Some(10)).to_string(), "It's ten!");
-assert_eq!(describe_optional_number(Some(3)).to_string(), "It's three or four!");
-assert_eq!(describe_optional_number(Some(4)).to_string(), "It's three or four!");
-assert_eq!(describe_optional_number(Some(0)).to_string(), "It's zero!");
-assert_eq!(describe_optional_number(Some(5)).to_string(), "It's some other value: 5");
-assert_eq!(describe_optional_number(None).to_string(), "It's not a number!");
+assert_eq!(greeting("World").to_string(), "<Hello, World!>");
 ```
 
-# Looping formatting
+[`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+*/
+#[macro_export]
+macro_rules! lazy_concat {
+    ($($fragment:expr),* $(,)?) => {
+        $crate::make_lazy_format!(|f| {
+            $(::core::fmt::Write::write_fmt(f, ::core::format_args!("{}", $fragment))?;)*
+            ::core::fmt::Result::Ok(())
+        })
+    };
+}
 
-`lazy_format!` supports formatting elements in a collection with a loop:
+/**
+Lazily format a value with its [`Debug`] implementation, for values that
+don't implement [`Display`]. The result implements [`Display`], so it can be
+used anywhere a lazy format can, such as an argument to [`lazy_format!`].
+As with [`lazy_format!`], the expression is captured, rather than evaluated,
+and is evaluated each time the value is formatted, and an argument written
+as `ref config` or `ref config.field` borrows the variable rather than
+moving it.
 
-```
-use std::fmt::Display;
-use lazy_format::lazy_format;
+The formatter is passed through as-is, so the alternate flag selects
+pretty-printed output: `{:#}` pretty-prints the value, and so does `{:#?}`,
+which (as with all lazy formats) renders the output rather than showing the
+macro call.
 
-let list = vec![1i32, 2, 3, 4];
-let list_ref = &list;
+Given several values, `lazy_debug!` formats them as a tuple, the same way as
+[`Formatter::debug_tuple`], without needing to build a tuple of the values.
 
-// Format each element in the iterable without additional arguments to `format_args`
-let simple_semicolons = lazy_format!("{v}; " for v in list_ref.iter().map(|x| x - 1));
-assert_eq!(simple_semicolons.to_string(), "0; 1; 2; 3; ");
+# Example
 
-// Perform a full format with additional arguments on each element in the iterable.
-let header = "Value";
-let full_format = lazy_format!(("{}: {}; ", header, v) for v in list_ref);
-assert_eq!(full_format.to_string(), "Value: 1; Value: 2; Value: 3; Value: 4; ");
 ```
+use lazy_format::{lazy_debug, lazy_format};
 
-Note that these looping formatters are not suitable for doing something like
-a comma separated list, since they'll apply the formatting to all elements.
-For a lazy string joining library, which only inserts separators between
-elements in a list, check out [joinery](/joinery).
+#[derive(Debug)]
+struct Config {
+    name: String,
+    retries: u32,
+}
+
+let config = Config { name: "main".to_owned(), retries: 3 };
+let debug = lazy_debug!(ref config);
+
+assert_eq!(debug.to_string(), r#"Config { name: "main", retries: 3 }"#);
+assert_eq!(
+    format!("{:#}", debug),
+    "Config {\n    name: \"main\",\n    retries: 3,\n}",
+);
+
+let message = lazy_format!("loaded {}", lazy_debug!(ref config.name, config.retries));
+assert_eq!(message.to_string(), r#"loaded ("main", 3)"#);
+```
 
-[`format!`]: https://doc.rust-lang.org/std/macro.format.html
-[`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
 [`Debug`]: https://doc.rust-lang.org/std/fmt/trait.Debug.html
-[`String`]: https://doc.rust-lang.org/std/string/struct.String.html
-[`File`]: https://doc.rust-lang.org/std/fs/struct.File.html
+[`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+[`Formatter::debug_tuple`]: https://doc.rust-lang.org/std/fmt/struct.Formatter.html#method.debug_tuple
 */
 #[macro_export]
-macro_rules! lazy_format {
-    // Basic lazy format: collect $args and format via `$pattern` when writing
-    // to a destination
-    ($pattern:literal $(, $($args:tt)*)?) => {
-        $crate::make_lazy_format!(|f| $crate::write!(f, $pattern $(, $($args)*)?))
-    };
+macro_rules! lazy_debug {
+    // The values are parsed one at a time, so that `ref` values can be
+    // borrowed before the closure, just like in `lazy_format!`.
+    (@args [$($borrows:tt)*] [$value:expr,] $(,)?) => {{
+        $($borrows)*
+        $crate::make_lazy_format!(|f| ::core::fmt::Debug::fmt(&$value, f))
+    }};
 
-    // Conditional lazy format: evaluate a match expression and format based on
-    // the matching arm
-    (match ($condition:expr) {
-        $($(
-            $match_pattern:pat
-            $(if $guard:expr)?
-            => $output:tt
-        ),+ $(,)?)?
-    }) => {
-        $crate::make_lazy_format!(|f| match $condition {
-            $($(
-                $match_pattern
-                $(if $guard)?
-                => $crate::write_tt!(f, $output),
-            )+)?
+    (@args [$($borrows:tt)*] [$($value:expr,)+] $(,)?) => {{
+        $($borrows)*
+        $crate::make_lazy_format!(|f| {
+            f.debug_tuple("")
+                $(.field(&$value))+
+                .finish()
         })
+    }};
+
+    (@args [$($borrows:tt)*] [$($values:tt)*] ref $name:ident $(. $field:tt)* $(, $($rest:tt)*)?) => {
+        $crate::lazy_debug!(
+            @args [$($borrows)* let borrowed = &$name $(. $field)*;] [$($values)* borrowed,]
+            $($($rest)*)?
+        )
     };
 
-    // Conditional pattern lazy format: evaluate
+    (@args [$($borrows:tt)*] [$($values:tt)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::lazy_debug!(@args [$($borrows)*] [$($values)* $value,] $($($rest)*)?)
+    };
 
-    // Conditional lazy format: evaluate an if / else if / else expression and
-    // format based on the successful branch
-    (
-        if $(let $match:pat = )? $condition:expr => $output:tt
-        $(else if $(let $elseif_match:pat = )? $elseif_condition:expr => $elseif_output:tt)*
-        $(else => $else_output:tt)?
-    ) => {
-        $crate::make_lazy_format!(|f|
-            if $(let $match = )? $condition {
-                $crate::write_tt!(f, $output)
-            }
-            $(else if $(let $elseif_match = )? $elseif_condition {
-                $crate::write_tt!(f, $elseif_output)
-            })*
-            $(else if true {
-                $crate::write_tt!(f, $else_output)
-            })?
-            else {
-                ::core::fmt::Result::Ok(())
-            }
-        )
+    ($($values:tt)+) => {
+        $crate::lazy_debug!(@args [] [] $($values)+)
     };
+}
+
+/**
+Lazily format a value after transforming it. `value` is evaluated once, when
+the lazy format is created, and captured; each time the lazy format is
+formatted, `transform` is called with a reference to it, and its output is
+formatted with `pattern` as the first positional argument. Any further
+arguments are passed along after it, just like with [`lazy_format!`].
+
+This is equivalent to a [`make_lazy_format!`] that calls `transform` and
+then writes the result, but reads better for the common case of a single
+transform. Because the transform is run each time the value is formatted,
+it can depend on state that changes between renders.
+
+# Example
+
+```
+use std::cell::Cell;
+use lazy_format::lazy_map;
+
+let scale = Cell::new(2);
+let price = lazy_map!(21, |cents| cents * scale.get(), "${}.00");
+
+assert_eq!(price.to_string(), "$42.00");
+scale.set(3);
+assert_eq!(price.to_string(), "$63.00");
+
+let padded = lazy_map!("abc", |s| s.len(), "{:>width$}", width = 4);
+assert_eq!(padded.to_string(), "   3");
+```
+*/
+#[macro_export]
+macro_rules! lazy_map {
+    ($value:expr, $transform:expr, $pattern:literal $(, $($args:tt)*)?) => {{
+        let value = $value;
+        let transform = $crate::typed_transform(&value, $transform);
 
-    // Looping formatter: format each `$item` in `$collection` with the format
-    // arguments
-    ($output:tt for $item:pat in $collection:expr) => {
         $crate::make_lazy_format!(|f| {
-            let mut iter = ::core::iter::IntoIterator::into_iter($collection);
-            ::core::iter::Iterator::try_for_each(&mut iter, |$item| $crate::write_tt!(f, $output))
+            let mapped = transform(&value);
+            $crate::write!(f, $pattern, mapped, $($($args)*)?)
         })
+    }};
+}
+
+/**
+Lazily join a slice of plain strings with a separator, such as the column
+names of a table header defined as a `const`. This creates a
+[`LazyCols`][crate::LazyCols], which writes the strings directly without
+allocating, and which can be created in a const context. For anything more
+complex than joining plain strings, see the looping forms of
+[`lazy_format!`].
+
+# Example
+
+```
+use lazy_format::{lazy_cols, LazyCols};
+
+const COLUMNS: &[&str] = &["Name", "Size", "Modified"];
+const HEADER: LazyCols = lazy_cols!(COLUMNS, " | ");
+
+assert_eq!(HEADER.to_string(), "Name | Size | Modified");
+assert_eq!(lazy_cols!(&["only"], ", ").to_string(), "only");
+```
+*/
+#[macro_export]
+macro_rules! lazy_cols {
+    ($columns:expr, $separator:expr $(,)?) => {
+        $crate::LazyCols::new($columns, $separator)
     };
 }
 
+/**
+Assert that a lazy format (or any other [`Display`] value) is `'static`,
+which means that it owns all of its captures rather than borrowing any of
+them. This is an identity function, so it has no cost; its only purpose is
+to give a clear compile error at the point where the lazy format is
+created, rather than wherever it's later stored in some long-lived
+structure or sent to another thread.
+
+# Example
+
+```
+use std::fmt::Display;
+use lazy_format::{assert_static, lazy_format};
+
+let name = String::from("Ferris");
+let greeting = assert_static(lazy_format!("Hello, {name}!"));
+
+let stored: Box<dyn Display> = Box::new(greeting);
+assert_eq!(stored.to_string(), "Hello, Ferris!");
+```
+
+A lazy format that borrows a local variable, such as through a `ref name`
+argument, is rejected:
+
+```compile_fail,E0597
+use lazy_format::{assert_static, lazy_format};
+
+let name = String::from("Ferris");
+
+// error: `name` does not live long enough
+let greeting = assert_static(lazy_format!("Hello, {}!", ref name));
+```
+
+[`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+*/
+#[inline(always)]
+pub fn assert_static<T: core::fmt::Display + 'static>(lazy: T) -> T {
+    lazy
+}
+
+mod adapters;
+#[cfg(feature = "alloc")]
+mod builder;
+mod count;
+mod debug;
+mod ext;
+#[cfg(feature = "std")]
+pub mod io;
+mod list;
+mod literal;
+pub mod number;
+pub mod runtime;
+pub mod transform;
+mod try_format;
+
+#[cfg(feature = "alloc")]
+pub use builder::{BuiltFormat, LazyBuilder};
+pub use count::{count_renders, CountRenders};
+pub use debug::{lazy_debug_list, lazy_debug_map, DebugList, DebugMap};
+pub use ext::{BufferFull, LazyFormatExt, RenderError};
+#[cfg(feature = "heapless")]
+pub use ext::CapacityError;
+#[cfg(feature = "alloc")]
+pub use ext::TooLong;
+#[cfg(feature = "std")]
+pub use io::write_lazy;
+pub use list::{oxford_list, OxfordList};
+pub use literal::{LazyCols, LazyStr};
+#[cfg(feature = "alloc")]
+pub use literal::LazyString;
+pub use try_format::{fallible, TryFormat};
+
 pub mod prelude {
-    pub use crate::{lazy_format, make_lazy_format};
+    pub use crate::{
+        lazy, lazy_concat, lazy_format, lazy_map, make_lazy_format, make_lazy_try_format,
+        LazyFormatExt,
+    };
 }
@@ -0,0 +1,94 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use core::fmt::{self, Display, Formatter};
+
+/**
+Lazily format the items of a collection as a natural-language list, like
+"a, b, and c". `separator` is written between the items, except before the
+last one, which is preceded by `last_separator` instead; a list of exactly two
+items is joined with `pair_separator`. The collection is cloned and iterated
+each time the value is formatted, so it should usually be a reference or a
+cheap iterator.
+
+Unlike a `sep` loop in [`lazy_format!`][crate::lazy_format], this needs to
+know which item is the last, so it looks one item ahead as it goes.
+
+# Example
+
+```
+use lazy_format::oxford_list;
+
+let list = |items: &[&str]| oxford_list(items.to_vec(), ", ", ", and ", " and ").to_string();
+
+assert_eq!(list(&[]), "");
+assert_eq!(list(&["tea"]), "tea");
+assert_eq!(list(&["tea", "coffee"]), "tea and coffee");
+assert_eq!(list(&["tea", "coffee", "juice"]), "tea, coffee, and juice");
+```
+*/
+#[inline]
+pub fn oxford_list<'a, I>(
+    items: I,
+    separator: &'a str,
+    last_separator: &'a str,
+    pair_separator: &'a str,
+) -> OxfordList<'a, I>
+where
+    I: IntoIterator + Clone,
+    I::Item: Display,
+{
+    OxfordList {
+        items,
+        separator,
+        last_separator,
+        pair_separator,
+    }
+}
+
+/// Display wrapper that formats a collection as a natural-language list.
+/// Created with [`oxford_list`].
+#[derive(Debug, Clone, Copy)]
+pub struct OxfordList<'a, I> {
+    items: I,
+    separator: &'a str,
+    last_separator: &'a str,
+    pair_separator: &'a str,
+}
+
+impl<I> Display for OxfordList<'_, I>
+where
+    I: IntoIterator + Clone,
+    I::Item: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut iter = self.items.clone().into_iter();
+
+        let first = match iter.next() {
+            Some(first) => first,
+            None => return Ok(()),
+        };
+
+        fmt::Write::write_fmt(f, format_args!("{}", first))?;
+
+        // Each item is only written once we know whether it's the last one
+        let mut pending = match iter.next() {
+            Some(second) => second,
+            None => return Ok(()),
+        };
+        let mut before_last = self.pair_separator;
+
+        for item in iter {
+            f.write_str(self.separator)?;
+            fmt::Write::write_fmt(f, format_args!("{}", pending))?;
+            pending = item;
+            before_last = self.last_separator;
+        }
+
+        f.write_str(before_last)?;
+        fmt::Write::write_fmt(f, format_args!("{}", pending))
+    }
+}
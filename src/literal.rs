@@ -0,0 +1,324 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use core::fmt::{self, Display, Formatter};
+use core::ops::Deref;
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String};
+
+/**
+A lazy format of a plain string, with no placeholders. This is the type
+returned by [`lazy_format!`] when it's called with only a format string that
+doesn't contain any placeholders, like `lazy_format!("Hello, World!")`.
+Because there's nothing to capture, the string is stored directly.
+
+Unlike other lazy formats, a `LazyStr` respects the width, fill, alignment,
+and precision of the [`Formatter`] it's written to, the same way a `&str`
+does.
+
+```
+use lazy_format::lazy_format;
+
+let greeting = lazy_format!("Hello");
+assert_eq!(format!("[{:>7}]", greeting), "[  Hello]");
+assert_eq!(format!("[{:.3}]", greeting), "[Hel]");
+```
+
+[`lazy_format!`]: crate::lazy_format
+*/
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[must_use = "lazy formats do nothing until they're formatted"]
+pub struct LazyStr(&'static str);
+
+impl LazyStr {
+    /// Create a new `LazyStr` from a plain string.
+    #[inline]
+    pub const fn new(s: &'static str) -> Self {
+        Self(s)
+    }
+
+    /**
+    Get the string that this lazy format writes. This is the format string
+    that was passed to [`lazy_format!`], with any `{{` or `}}` escapes
+    resolved. It can be used in a const context, which makes it useful for
+    tools (like translation string extractors) that need to enumerate the
+    strings in a program.
+
+    # Example
+
+    ```
+    use lazy_format::{lazy_format, LazyStr};
+
+    let braces = lazy_format!("{{ braces }}");
+    assert_eq!(braces.as_str(), "{ braces }");
+
+    const GREETINGS: [&str; 2] = [
+        LazyStr::new("Hello").as_str(),
+        LazyStr::new("Goodbye").as_str(),
+    ];
+    assert_eq!(GREETINGS, ["Hello", "Goodbye"]);
+    ```
+
+    [`lazy_format!`]: crate::lazy_format
+    */
+    #[inline]
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        self.0
+    }
+
+    /**
+    Get the output of this lazy format as a [`Cow`]. Because the output is a
+    plain string, this is always [`Cow::Borrowed`]; other lazy formats have
+    to be rendered to a [`Cow::Owned`] (see
+    [`LazyFormatExt::as_cow`][crate::LazyFormatExt::as_cow]).
+
+    # Example
+
+    ```
+    use std::borrow::Cow;
+    use lazy_format::lazy_format;
+
+    let greeting = lazy_format!("Hello, World!");
+    assert!(matches!(greeting.as_cow(), Cow::Borrowed("Hello, World!")));
+    ```
+
+    [`Cow`]: https://doc.rust-lang.org/std/borrow/enum.Cow.html
+    [`Cow::Borrowed`]: https://doc.rust-lang.org/std/borrow/enum.Cow.html#variant.Borrowed
+    [`Cow::Owned`]: https://doc.rust-lang.org/std/borrow/enum.Cow.html#variant.Owned
+    */
+    #[cfg(feature = "alloc")]
+    #[inline]
+    #[must_use]
+    pub fn as_cow(&self) -> Cow<'static, str> {
+        Cow::Borrowed(self.0)
+    }
+}
+
+impl Display for LazyStr {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.pad(self.0)
+    }
+}
+
+// Like other lazy formats, the alternate Debug (`{:#?}`) renders the value,
+// and the regular one shows the macro invocation, with the braces escaped
+// again.
+impl fmt::Debug for LazyStr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return Display::fmt(self, f);
+        }
+
+        f.write_str("lazy_format!(\"")?;
+        self.0.chars().try_for_each(|c| match c {
+            '{' => f.write_str("{{"),
+            '}' => f.write_str("}}"),
+            '\'' => f.write_str("'"),
+            c => Display::fmt(&c.escape_debug(), f),
+        })?;
+        f.write_str("\")")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for LazyStr {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl From<&'static str> for LazyStr {
+    #[inline]
+    fn from(s: &'static str) -> Self {
+        Self(s)
+    }
+}
+
+impl AsRef<str> for LazyStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+/**
+A `LazyStr` can be used anywhere a `&str` is expected.
+
+```
+use lazy_format::lazy_format;
+
+let greeting = lazy_format!("Hello, World!");
+assert!(greeting.starts_with("Hello"));
+assert_eq!(greeting.len(), 13);
+```
+*/
+impl Deref for LazyStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+/**
+A lazy format of a list of plain strings, joined with a separator, like the
+column names of a table header. Usually created with [`lazy_cols!`]. It can
+be created in a const context, and writes the strings directly, without
+allocating.
+
+[`lazy_cols!`]: crate::lazy_cols
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LazyCols<'a> {
+    columns: &'a [&'a str],
+    separator: &'a str,
+}
+
+impl<'a> LazyCols<'a> {
+    /// Create a new `LazyCols` that writes `columns`, with `separator`
+    /// between each of them.
+    #[inline]
+    #[must_use]
+    pub const fn new(columns: &'a [&'a str], separator: &'a str) -> Self {
+        Self { columns, separator }
+    }
+
+    /// Get the strings that this lazy format writes.
+    #[inline]
+    #[must_use]
+    pub const fn columns(&self) -> &'a [&'a str] {
+        self.columns
+    }
+}
+
+impl Display for LazyCols<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut columns = self.columns.iter();
+
+        if let Some(first) = columns.next() {
+            f.write_str(first)?;
+            columns.try_for_each(|column| {
+                f.write_str(self.separator)?;
+                f.write_str(column)
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for LazyCols<'_> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/**
+An owned counterpart to [`LazyStr`], for strings that are only known at
+runtime. Like a [`LazyStr`], it respects the width, fill, alignment, and
+precision of the [`Formatter`] it's written to, and it can be used anywhere
+a `&str` is expected. This makes it possible for an API to accept a single
+type for both literal and dynamic strings, with `impl Into<LazyString>`.
+Requires the `alloc` feature.
+
+# Example
+
+```
+use lazy_format::{lazy_format, LazyString};
+
+fn label(text: impl Into<LazyString>) -> LazyString {
+    text.into()
+}
+
+let name = String::from("dynamic");
+assert_eq!(format!("[{:>9}]", label(name)), "[  dynamic]");
+assert_eq!(format!("[{:<9}]", label(lazy_format!("literal"))), "[literal  ]");
+```
+*/
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LazyString(String);
+
+#[cfg(feature = "alloc")]
+impl LazyString {
+    /// Create a new `LazyString` from an owned string.
+    #[inline]
+    #[must_use]
+    pub const fn new(s: String) -> Self {
+        Self(s)
+    }
+
+    /// Get the string that this lazy format writes.
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Convert this `LazyString` back into the [`String`] it contains.
+    #[inline]
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Display for LazyString {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "serde"))]
+impl serde::Serialize for LazyString {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<String> for LazyString {
+    #[inline]
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<LazyStr> for LazyString {
+    #[inline]
+    fn from(s: LazyStr) -> Self {
+        Self(s.0.into())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<str> for LazyString {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Deref for LazyString {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
@@ -0,0 +1,415 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Lazy formatting for numbers. Like the rest of this crate, these don't
+allocate: numbers are formatted into small buffers on the stack, and then
+written directly into the final destination.
+*/
+
+use core::fmt::{self, Alignment, Display, Formatter, Write as _};
+use core::time::Duration;
+
+use crate::adapters::SliceWriter;
+
+/**
+Primitive integers that can be formatted by the helpers in this module
+(every integer type up to 64 bits).
+*/
+pub trait Integer: Copy {
+    /// Split this integer into whether it's negative, and its magnitude.
+    fn into_sign_magnitude(self) -> (bool, u64);
+}
+
+macro_rules! impl_integer {
+    (unsigned: $($type:ty)*; signed: $($signed:ty)*) => {
+        $(impl Integer for $type {
+            #[inline]
+            fn into_sign_magnitude(self) -> (bool, u64) {
+                (false, self as u64)
+            }
+        })*
+
+        $(impl Integer for $signed {
+            #[inline]
+            fn into_sign_magnitude(self) -> (bool, u64) {
+                (self < 0, self.unsigned_abs() as u64)
+            }
+        })*
+    };
+}
+
+impl_integer! {
+    unsigned: u8 u16 u32 u64 usize;
+    signed: i8 i16 i32 i64 isize
+}
+
+/**
+Format an integer with `separator` between each group of 3 digits, like
+`1,234,567`. The formatter's width, fill, alignment, and `+` flag are
+respected, just like when formatting the integer itself.
+
+# Example
+
+```
+use lazy_format::number::group_digits;
+
+assert_eq!(group_digits(1234567, ',').to_string(), "1,234,567");
+assert_eq!(group_digits(-1000i64, '_').to_string(), "-1_000");
+assert_eq!(group_digits(255u8, ',').to_string(), "255");
+assert_eq!(format!("[{:>10}]", group_digits(123456, ' ')), "[   123 456]");
+```
+*/
+#[inline]
+pub fn group_digits<T: Integer>(value: T, separator: char) -> GroupDigits<T> {
+    GroupDigits { value, separator }
+}
+
+/// Display wrapper that formats an integer with digit group separators.
+/// Created with [`group_digits`].
+#[derive(Debug, Clone, Copy)]
+pub struct GroupDigits<T> {
+    value: T,
+    separator: char,
+}
+
+impl<T: Integer> Display for GroupDigits<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // u64::MAX has 20 digits, so there are at most 6 separators of at
+        // most 4 bytes each.
+        let mut digits = [0u8; 20];
+        let mut buffer = [0u8; 20 + 6 * 4];
+
+        let (negative, mut magnitude) = self.value.into_sign_magnitude();
+
+        let mut start = digits.len();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (magnitude % 10) as u8;
+            magnitude /= 10;
+
+            if magnitude == 0 {
+                break;
+            }
+        }
+
+        let digits = &digits[start..];
+        let mut separator = [0u8; 4];
+        let separator = self.separator.encode_utf8(&mut separator).as_bytes();
+
+        let mut len = 0;
+        for (i, &digit) in digits.iter().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                buffer[len..len + separator.len()].copy_from_slice(separator);
+                len += separator.len();
+            }
+
+            buffer[len] = digit;
+            len += 1;
+        }
+
+        let grouped = core::str::from_utf8(&buffer[..len]).map_err(|_| fmt::Error)?;
+        f.pad_integral(!negative, "", grouped)
+    }
+}
+
+/// Write `args` into `buffer`, and then write the result to `f` with its
+/// padding. `buffer` must be large enough for anything written to it.
+fn pad_buffered(f: &mut Formatter<'_>, buffer: &mut [u8], args: fmt::Arguments<'_>) -> fmt::Result {
+    let mut dest = SliceWriter {
+        buffer,
+        len: 0,
+        full: false,
+    };
+    dest.write_fmt(args)?;
+
+    let len = dest.len;
+    let written = core::str::from_utf8(&buffer[..len]).map_err(|_| fmt::Error)?;
+    f.pad(written)
+}
+
+/**
+Format a number of bytes in human-readable binary units, like `1.5 KiB`.
+Amounts under 1 KiB are written as a whole number of bytes (`1023 B`);
+larger amounts are written in the largest unit (up to EiB) that they're at
+least 1 of, rounded to the nearest tenth. The formatter's width, fill, and
+alignment are respected.
+
+# Example
+
+```
+use lazy_format::number::human_bytes;
+
+assert_eq!(human_bytes(512).to_string(), "512 B");
+assert_eq!(human_bytes(1536).to_string(), "1.5 KiB");
+assert_eq!(human_bytes(3 * 1024 * 1024).to_string(), "3.0 MiB");
+assert_eq!(format!("[{:>9}]", human_bytes(2048)), "[  2.0 KiB]");
+```
+*/
+#[inline]
+pub fn human_bytes(bytes: u64) -> HumanBytes {
+    HumanBytes { bytes }
+}
+
+/// Display wrapper that formats a number of bytes in human-readable units.
+/// Created with [`human_bytes`].
+#[derive(Debug, Clone, Copy)]
+pub struct HumanBytes {
+    bytes: u64,
+}
+
+impl Display for HumanBytes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+        // The output is at most 10 bytes long, like "1023.9 KiB"
+        let mut buffer = [0u8; 16];
+
+        let bytes = self.bytes;
+        let mut unit = 0;
+        while unit + 1 < UNITS.len() && bytes >= 1 << (10 * (unit + 1)) {
+            unit += 1;
+        }
+
+        if unit == 0 {
+            return pad_buffered(f, &mut buffer, format_args!("{} B", bytes));
+        }
+
+        let tenths = |unit: usize| {
+            let divisor = 1u128 << (10 * unit);
+            (bytes as u128 * 10 + divisor / 2) / divisor
+        };
+
+        // Rounding may carry over into the next unit, like 1023.96 KiB
+        let mut rounded = tenths(unit);
+        if rounded >= 10240 && unit + 1 < UNITS.len() {
+            unit += 1;
+            rounded = tenths(unit);
+        }
+
+        pad_buffered(
+            f,
+            &mut buffer,
+            format_args!("{}.{} {}", rounded / 10, rounded % 10, UNITS[unit]),
+        )
+    }
+}
+
+/**
+Format a [`Duration`] in human-readable units, like `2m 3s`. The duration is
+written with its largest nonzero unit (days, hours, minutes, or seconds) and
+the next one down, truncating anything smaller: `1h 0m`, `59s`. Durations
+under a second are written in whole milliseconds, microseconds, or
+nanoseconds, like `250ms`. The formatter's width, fill, and alignment are
+respected.
+
+# Example
+
+```
+use std::time::Duration;
+use lazy_format::number::human_duration;
+
+assert_eq!(human_duration(Duration::from_secs(123)).to_string(), "2m 3s");
+assert_eq!(human_duration(Duration::from_secs(7200)).to_string(), "2h 0m");
+assert_eq!(human_duration(Duration::from_millis(1500)).to_string(), "1s");
+assert_eq!(human_duration(Duration::from_micros(250)).to_string(), "250µs");
+assert_eq!(format!("[{:<7}]", human_duration(Duration::ZERO)), "[0s     ]");
+```
+
+[`Duration`]: https://doc.rust-lang.org/std/time/struct.Duration.html
+*/
+#[inline]
+pub fn human_duration(duration: Duration) -> HumanDuration {
+    HumanDuration { duration }
+}
+
+/// Display wrapper that formats a [`Duration`] in human-readable units.
+/// Created with [`human_duration`].
+///
+/// [`Duration`]: https://doc.rust-lang.org/std/time/struct.Duration.html
+#[derive(Debug, Clone, Copy)]
+pub struct HumanDuration {
+    duration: Duration,
+}
+
+impl Display for HumanDuration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // The output is at most 20 bytes long, like "213503982334601d 23h"
+        let mut buffer = [0u8; 24];
+
+        let secs = self.duration.as_secs();
+        let nanos = self.duration.subsec_nanos();
+
+        let (major, minor) = match (secs / 86400, secs / 3600, secs / 60) {
+            (0, 0, 0) if secs > 0 => ((secs, "s"), None),
+            (0, 0, 0) => match nanos {
+                0 => ((0, "s"), None),
+                1..=999 => ((nanos.into(), "ns"), None),
+                1000..=999_999 => (((nanos / 1000).into(), "µs"), None),
+                _ => (((nanos / 1_000_000).into(), "ms"), None),
+            },
+            (0, 0, minutes) => ((minutes, "m"), Some((secs % 60, "s"))),
+            (0, hours, _) => ((hours, "h"), Some((secs / 60 % 60, "m"))),
+            (days, _, _) => ((days, "d"), Some((secs / 3600 % 24, "h"))),
+        };
+
+        match minor {
+            None => pad_buffered(f, &mut buffer, format_args!("{}{}", major.0, major.1)),
+            Some(minor) => pad_buffered(
+                f,
+                &mut buffer,
+                format_args!("{}{} {}{}", major.0, major.1, minor.0, minor.1),
+            ),
+        }
+    }
+}
+
+/**
+Format `value` in a field exactly `width` characters wide, like a cell in a
+spreadsheet. If the formatted value is narrower than `width`, it's padded,
+using the fill and alignment of the formatter (right-aligned with spaces by
+default). If it's wider, the field is filled with `overflow` instead, so
+that a value that doesn't fit is never cut off or allowed to push the rest
+of the line out of place.
+
+The value is formatted with `{}` into a 64-byte buffer on the stack; values
+longer than that are treated as overflowing, even if `width` is larger.
+Format the value with another lazy format to control its precision, sign,
+and so on.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::number::fixed_num;
+
+assert_eq!(fixed_num(42, 5, '#').to_string(), "   42");
+assert_eq!(fixed_num(123456, 5, '#').to_string(), "#####");
+assert_eq!(format!("{:<5}|", fixed_num(-1, 5, '#')), "-1   |");
+
+let ratio = 2.0 / 3.0;
+assert_eq!(fixed_num(lazy_format!("{:.2}", ratio), 6, '*').to_string(), "  0.67");
+```
+*/
+#[inline]
+pub fn fixed_num<T: Display>(value: T, width: usize, overflow: char) -> FixedNum<T> {
+    FixedNum {
+        value,
+        width,
+        overflow,
+    }
+}
+
+/// Display wrapper that formats a value in a fixed-width field, or an
+/// overflow marker if it doesn't fit. Created with [`fixed_num`].
+#[derive(Debug, Clone, Copy)]
+pub struct FixedNum<T> {
+    value: T,
+    width: usize,
+    overflow: char,
+}
+
+impl<T: Display> Display for FixedNum<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut buffer = [0u8; 64];
+        let mut dest = SliceWriter {
+            buffer: &mut buffer,
+            len: 0,
+            full: false,
+        };
+
+        let rendered = match dest.write_fmt(format_args!("{}", self.value)) {
+            Ok(()) => {
+                let len = dest.len;
+                Some(core::str::from_utf8(&buffer[..len]).map_err(|_| fmt::Error)?)
+            }
+            Err(_) if dest.full => None,
+            Err(err) => return Err(err),
+        };
+
+        let (rendered, len) = match rendered.map(|rendered| (rendered, rendered.chars().count())) {
+            Some((rendered, len)) if len <= self.width => (rendered, len),
+            _ => return (0..self.width).try_for_each(|_| f.write_char(self.overflow)),
+        };
+
+        let padding = self.width - len;
+        let (before, after) = match f.align() {
+            Some(Alignment::Left) => (0, padding),
+            Some(Alignment::Center) => (padding / 2, padding - padding / 2),
+            Some(Alignment::Right) | None => (padding, 0),
+        };
+
+        let fill = f.fill();
+        (0..before).try_for_each(|_| f.write_char(fill))?;
+        f.write_str(rendered)?;
+        (0..after).try_for_each(|_| f.write_char(fill))
+    }
+}
+
+/**
+Format `done` out of `total` as a percentage with `decimals` decimal places,
+like `42.5%`. The percentage is computed with integer arithmetic, rounding
+half up, so it's exact for any inputs, and amounts over 100% (when `done` is
+more than `total`) are written as they are. When `total` is 0 the percentage
+is undefined, so it's written as `--%`. At most 9 decimal places are
+written; larger values of `decimals` are treated as 9. The formatter's
+width, fill, and alignment are respected.
+
+# Example
+
+```
+use lazy_format::number::percent;
+
+assert_eq!(percent(1, 3, 1).to_string(), "33.3%");
+assert_eq!(percent(2, 3, 0).to_string(), "67%");
+assert_eq!(percent(1, 8, 3).to_string(), "12.500%");
+assert_eq!(percent(0, 0, 1).to_string(), "--%");
+assert_eq!(format!("[{:>6}]", percent(5, 10, 1)), "[ 50.0%]");
+```
+*/
+#[inline]
+pub fn percent(done: u64, total: u64, decimals: usize) -> Percent {
+    Percent {
+        done,
+        total,
+        decimals: decimals.min(9),
+    }
+}
+
+/// Display wrapper that formats a ratio as a percentage. Created with
+/// [`percent`].
+#[derive(Debug, Clone, Copy)]
+pub struct Percent {
+    done: u64,
+    total: u64,
+    decimals: usize,
+}
+
+impl Display for Percent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.total == 0 {
+            return f.pad("--%");
+        }
+
+        // The output is at most 33 bytes long: 22 digits for u64::MAX * 100,
+        // a decimal point, 9 decimal places, and the percent sign
+        let mut buffer = [0u8; 40];
+
+        let scale = 10u128.pow(self.decimals as u32);
+        let total = u128::from(self.total);
+        let scaled = (u128::from(self.done) * 100 * scale + total / 2) / total;
+        let (whole, fraction) = (scaled / scale, scaled % scale);
+
+        match self.decimals {
+            0 => pad_buffered(f, &mut buffer, format_args!("{}%", whole)),
+            decimals => pad_buffered(
+                f,
+                &mut buffer,
+                format_args!("{}.{:0decimals$}%", whole, fraction),
+            ),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Lazy formatting with templates that are only known at runtime, such as
+templates loaded from configuration or translation files.
+
+Unlike [`lazy_format!`][crate::lazy_format], nothing here can be checked at
+compile time. The template is parsed every time the value is formatted, and
+any problem with it (an unclosed brace, a reference to a missing argument,
+etc) is reported as a [`fmt::Error`] from the [`Display`] implementation.
+
+The supported template syntax is a small subset of the [`format!`] syntax:
+
+- `{}` writes the next positional argument
+- `{0}`, `{1}`, ... write the positional argument at that index
+- `{name}` writes the named argument called `name`
+- `{{` and `}}` write a literal `{` or `}`
+
+Format specs (like `{:>10}`) are not supported.
+
+[`format!`]: https://doc.rust-lang.org/std/macro.format.html
+*/
+
+use core::fmt::{self, Display, Formatter};
+
+/**
+A lazily formatted runtime template. Created with [`lazy_runtime`] or
+[`lazy_runtime_named`]; see the [module documentation][self] for the
+template syntax.
+*/
+#[derive(Clone, Copy)]
+pub struct RuntimeFormat<'a> {
+    template: &'a str,
+    args: &'a [&'a dyn Display],
+    named: &'a [(&'a str, &'a dyn Display)],
+}
+
+/**
+Lazily format a runtime `template` with positional `args`.
+
+# Example
+
+```
+use lazy_format::runtime::lazy_runtime;
+
+let template = String::from("{} + {} = {2}");
+let result = lazy_runtime(&template, &[&1, &2, &3]);
+assert_eq!(result.to_string(), "1 + 2 = 3");
+```
+
+Malformed templates fail when formatted, rather than when created:
+
+```
+use std::fmt::Write;
+use lazy_format::runtime::lazy_runtime;
+
+let result = lazy_runtime("{} {}", &[&1]);
+let mut dest = String::new();
+assert!(write!(&mut dest, "{}", result).is_err());
+```
+*/
+#[inline]
+pub fn lazy_runtime<'a>(template: &'a str, args: &'a [&'a dyn Display]) -> RuntimeFormat<'a> {
+    RuntimeFormat {
+        template,
+        args,
+        named: &[],
+    }
+}
+
+/**
+Lazily format a runtime `template` with positional `args` and `named`
+arguments.
+
+# Example
+
+```
+use lazy_format::runtime::lazy_runtime_named;
+
+let result = lazy_runtime_named(
+    "{greeting}, {}!",
+    &[&"World"],
+    &[("greeting", &"Hello")],
+);
+assert_eq!(result.to_string(), "Hello, World!");
+```
+*/
+#[inline]
+pub fn lazy_runtime_named<'a>(
+    template: &'a str,
+    args: &'a [&'a dyn Display],
+    named: &'a [(&'a str, &'a dyn Display)],
+) -> RuntimeFormat<'a> {
+    RuntimeFormat {
+        template,
+        args,
+        named,
+    }
+}
+
+impl RuntimeFormat<'_> {
+    fn lookup(&self, key: &str, next: &mut usize) -> Option<&dyn Display> {
+        if key.is_empty() {
+            let arg = self.args.get(*next);
+            *next += 1;
+            arg.copied()
+        } else if let Ok(index) = key.parse::<usize>() {
+            self.args.get(index).copied()
+        } else {
+            self.named
+                .iter()
+                .find(|&&(name, _)| name == key)
+                .map(|&(_, arg)| arg)
+        }
+    }
+}
+
+impl Display for RuntimeFormat<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut template = self.template;
+        let mut next = 0;
+
+        while let Some(idx) = template.find(['{', '}']) {
+            let (plain, tail) = template.split_at(idx);
+            f.write_str(plain)?;
+
+            template = match tail.as_bytes() {
+                [b'{', b'{', ..] => {
+                    f.write_str("{")?;
+                    &tail[2..]
+                }
+                [b'}', b'}', ..] => {
+                    f.write_str("}")?;
+                    &tail[2..]
+                }
+                [b'{', ..] => {
+                    let end = tail.find('}').ok_or(fmt::Error)?;
+                    let arg = self.lookup(&tail[1..end], &mut next).ok_or(fmt::Error)?;
+                    f.write_fmt(format_args!("{}", arg))?;
+                    &tail[end + 1..]
+                }
+                _ => return Err(fmt::Error),
+            };
+        }
+
+        f.write_str(template)
+    }
+}
+
+impl fmt::Debug for RuntimeFormat<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuntimeFormat")
+            .field("template", &self.template)
+            .finish_non_exhaustive()
+    }
+}
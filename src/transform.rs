@@ -0,0 +1,984 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Wrappers that transform the output of another [`Display`] value (usually a
+lazy format) as it's being written. Like the lazy formats themselves, these
+are lazy: nothing happens until they're formatted, and the output is
+transformed in a streaming fashion, directly into the final destination,
+without any intermediary allocations.
+*/
+
+use core::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use core::cell::OnceCell;
+
+use crate::adapters::{
+    write_spaces, AsciiCaseConverter, CharCounter, CharTally, CsvScanner, HtmlEscaper, Indenter,
+    JsonEscaper, LinePrefixer, NewlineReplacer, QuoteDoubler, Skipper, Truncator,
+};
+
+/**
+Indent each line of the output of `inner` by `indent` spaces. Empty lines
+are left empty.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::indent;
+
+let items = ["a", "b"];
+let list = lazy_format!("- {item}\n" for item in items);
+let nested = lazy_format!("items:\n{}", indent(4, list));
+
+assert_eq!(nested.to_string(), "items:\n    - a\n    - b\n");
+```
+*/
+#[inline]
+pub fn indent<T: Display>(indent: usize, inner: T) -> Indent<T> {
+    Indent { indent, inner }
+}
+
+/// Display wrapper that indents each line of its contents. Created with
+/// [`indent`].
+#[derive(Debug, Clone, Copy)]
+pub struct Indent<T> {
+    indent: usize,
+    inner: T,
+}
+
+impl<T: Display> Display for Indent<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut dest = Indenter {
+            dest: f,
+            indent: self.indent,
+            at_line_start: true,
+        };
+
+        fmt::Write::write_fmt(&mut dest, format_args!("{}", self.inner))
+    }
+}
+
+/**
+Write `prefix` at the start of each line of the output of `inner`, like the
+`> ` of a quoted email or Markdown block quote. Unlike [`indent`], empty
+lines are prefixed too, but a trailing newline at the very end of the output
+isn't followed by a prefix.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::prefix_lines;
+
+let reply = "Sounds good!\n\nSee you then.\n";
+let quoted = prefix_lines(lazy_format!("{reply}"), "> ");
+
+assert_eq!(quoted.to_string(), "> Sounds good!\n> \n> See you then.\n");
+```
+*/
+#[inline]
+pub fn prefix_lines<T: Display>(inner: T, prefix: &str) -> PrefixLines<'_, T> {
+    PrefixLines { inner, prefix }
+}
+
+/// Display wrapper that prefixes each line of its contents. Created with
+/// [`prefix_lines`].
+#[derive(Debug, Clone, Copy)]
+pub struct PrefixLines<'a, T> {
+    inner: T,
+    prefix: &'a str,
+}
+
+impl<T: Display> Display for PrefixLines<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut dest = LinePrefixer {
+            dest: f,
+            prefix: self.prefix,
+            at_line_start: true,
+        };
+
+        fmt::Write::write_fmt(&mut dest, format_args!("{}", self.inner))
+    }
+}
+
+/**
+Replace each line break in the output of `inner` with `separator`, to write
+multi-line output, like a backtrace, on a single line. Both `\n` and `\r\n`
+count as a line break, even when they're split between two separate writes;
+a lone `\r` is left alone.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::single_line;
+
+let frames = ["main", "run", "step"];
+let trace = lazy_format!("at {frame}\n" for frame in frames);
+
+assert_eq!(single_line(trace, " | ").to_string(), "at main | at run | at step | ");
+assert_eq!(single_line("a\r\nb", "; ").to_string(), "a; b");
+```
+*/
+#[inline]
+pub fn single_line<T: Display>(inner: T, separator: &str) -> SingleLine<'_, T> {
+    SingleLine { inner, separator }
+}
+
+/// Display wrapper that replaces the line breaks in its contents. Created
+/// with [`single_line`].
+#[derive(Debug, Clone, Copy)]
+pub struct SingleLine<'a, T> {
+    inner: T,
+    separator: &'a str,
+}
+
+impl<T: Display> Display for SingleLine<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut dest = NewlineReplacer {
+            dest: f,
+            separator: self.separator,
+            pending_cr: false,
+        };
+
+        fmt::Write::write_fmt(&mut dest, format_args!("{}", self.inner))?;
+        dest.finish()
+    }
+}
+
+/**
+Truncate the output of `inner` to at most `max_chars` chars, including
+`ellipsis`, which is written if there was more output than fits. Formatting
+of `inner` stops as soon as the limit is reached. If `ellipsis` is longer
+than `max_chars` by itself, only as much of it as fits is written.
+
+Chars are counted as unicode scalar values, so a multi-byte char is never
+split, but note that this is different from the display width of the text.
+
+The end of the output that only fits without the ellipsis is held back
+until it's known whether there's more output. If that's more than 64
+bytes, like with a long ellipsis, `inner` is formatted a second time to
+write it, so it should write the same output every time.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::truncate;
+
+let name = "Hello, World";
+assert_eq!(truncate(lazy_format!("{name}!"), 6, "…").to_string(), "Hello…");
+assert_eq!(truncate(lazy_format!("{name}!"), 8, "...").to_string(), "Hello...");
+assert_eq!(truncate(lazy_format!("{name}!"), 13, "…").to_string(), "Hello, World!");
+```
+*/
+#[inline]
+pub fn truncate<T: Display>(inner: T, max_chars: usize, ellipsis: &str) -> Truncate<'_, T> {
+    Truncate {
+        inner,
+        max_chars,
+        ellipsis,
+    }
+}
+
+/// Display wrapper that truncates its contents to a maximum number of chars.
+/// Created with [`truncate`].
+#[derive(Debug, Clone, Copy)]
+pub struct Truncate<'a, T> {
+    inner: T,
+    max_chars: usize,
+    ellipsis: &'a str,
+}
+
+impl<T: Display> Display for Truncate<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_truncated(f, &self.inner, self.max_chars, self.ellipsis, |_| 1)
+    }
+}
+
+/// Write `inner` to `dest`, truncated to `max_cols` columns as measured by
+/// `width`, including the ellipsis.
+fn write_truncated(
+    dest: &mut Formatter<'_>,
+    inner: &impl Display,
+    max_cols: usize,
+    ellipsis: &str,
+    width: fn(char) -> usize,
+) -> fmt::Result {
+    let mut truncator = Truncator::new(dest, max_cols, ellipsis, width);
+
+    match fmt::Write::write_fmt(&mut truncator, format_args!("{}", inner)) {
+        // The end of the output fits, but it was too long to hold on to, so
+        // format `inner` again to write it.
+        Ok(()) if truncator.overflowed => {
+            let mut rest = Skipper {
+                dest: truncator.dest,
+                skip: truncator.written,
+            };
+            fmt::Write::write_fmt(&mut rest, format_args!("{}", inner))
+        }
+        Ok(()) => truncator.finish(),
+        Err(fmt::Error) if truncator.truncated => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/**
+Truncate the output of `inner` to at most `max_cols` display columns, as
+measured by the [`unicode-width`] crate, including `ellipsis`, which is
+written if there was more output than fits, like with [`truncate`]. Wide characters, like most CJK
+characters, take up 2 columns, and a char is never split. Formatting of
+`inner` stops as soon as the limit is reached. Requires the `unicode-width`
+feature.
+
+Like with [`truncate`], if the end of the output that only fits without
+the ellipsis is more than 64 bytes, like with a long ellipsis or a run of
+zero-width chars, `inner` is formatted a second time to write it.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::truncate_cols;
+
+let name = "日本語のテキスト";
+assert_eq!(truncate_cols(lazy_format!("{name}"), 7, "…").to_string(), "日本語…");
+assert_eq!(truncate_cols(lazy_format!("{name}"), 16, "…").to_string(), name);
+```
+
+[`unicode-width`]: https://docs.rs/unicode-width
+*/
+#[cfg(feature = "unicode-width")]
+#[inline]
+pub fn truncate_cols<T: Display>(inner: T, max_cols: usize, ellipsis: &str) -> TruncateCols<'_, T> {
+    TruncateCols {
+        inner,
+        max_cols,
+        ellipsis,
+    }
+}
+
+/// Display wrapper that truncates its contents to a maximum number of display
+/// columns. Created with [`truncate_cols`].
+#[cfg(feature = "unicode-width")]
+#[derive(Debug, Clone, Copy)]
+pub struct TruncateCols<'a, T> {
+    inner: T,
+    max_cols: usize,
+    ellipsis: &'a str,
+}
+
+#[cfg(feature = "unicode-width")]
+impl<T: Display> Display for TruncateCols<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_truncated(f, &self.inner, self.max_cols, self.ellipsis, |c| {
+            unicode_width::UnicodeWidthChar::width(c).unwrap_or(0)
+        })
+    }
+}
+
+/**
+Escape the output of `inner` so that it can be safely included in HTML text
+or in a quoted attribute value. `&`, `<`, `>`, `"`, and `'` are replaced with
+their corresponding character references.
+
+# Example
+
+```
+use std::fmt::Display;
+
+use lazy_format::lazy_format;
+use lazy_format::transform::escape_html;
+
+fn html_tag<'a>(tag: &'a str, content: impl Display + 'a) -> impl Display + 'a {
+    let content = escape_html(content);
+    lazy_format!("<{tag}>{content}</{tag}>")
+}
+
+let result = html_tag("p", lazy_format!("{} & {}", "<b>", "'quoted'")).to_string();
+assert_eq!(result, "<p>&lt;b&gt; &amp; &#39;quoted&#39;</p>");
+```
+*/
+#[inline]
+pub fn escape_html<T: Display>(inner: T) -> EscapeHtml<T> {
+    EscapeHtml { inner }
+}
+
+/// Display wrapper that escapes HTML special characters in its contents.
+/// Created with [`escape_html`].
+#[derive(Debug, Clone, Copy)]
+pub struct EscapeHtml<T> {
+    inner: T,
+}
+
+impl<T: Display> Display for EscapeHtml<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut dest = HtmlEscaper { dest: f };
+        fmt::Write::write_fmt(&mut dest, format_args!("{}", self.inner))
+    }
+}
+
+/**
+Escape the output of `inner` as the contents of a JSON string. `"` and `\`
+are backslash-escaped, and control characters are written as their short
+escapes (like `\n`) or as `\u00XX`. The surrounding quotes are not included.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::escape_json_str;
+
+let message = "say \"hi\"\n";
+let json = lazy_format!("{{\"message\": \"{}\"}}", escape_json_str(message));
+
+assert_eq!(json.to_string(), r#"{"message": "say \"hi\"\n"}"#);
+```
+*/
+#[inline]
+pub fn escape_json_str<T: Display>(inner: T) -> EscapeJsonStr<T> {
+    EscapeJsonStr { inner }
+}
+
+/// Display wrapper that escapes its contents as the contents of a JSON
+/// string. Created with [`escape_json_str`].
+#[derive(Debug, Clone, Copy)]
+pub struct EscapeJsonStr<T> {
+    inner: T,
+}
+
+impl<T: Display> Display for EscapeJsonStr<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut dest = JsonEscaper { dest: f };
+        fmt::Write::write_fmt(&mut dest, format_args!("{}", self.inner))
+    }
+}
+
+/**
+Escape the output of `inner` as a CSV field. If it contains a comma, a
+double quote, or a line break, it's wrapped in double quotes, and any double
+quotes in it are doubled; otherwise it's written unchanged.
+
+Whether the field needs quotes can't be known until all of it has been
+seen, so rather than buffering the output, `inner` is formatted twice: once
+to check whether it needs quotes (stopping early at the first char that
+does), and once to write it. This means that `inner` should produce the same
+output every time it's formatted; use [`render_once`] for a value that
+doesn't.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::escape_csv;
+
+let fields = ["plain", "with, comma", "say \"hi\""];
+let row = lazy_format!(("{}", escape_csv(field)) sep "," for field in fields);
+
+assert_eq!(row.to_string(), r#"plain,"with, comma","say ""hi""""#);
+```
+*/
+#[inline]
+pub fn escape_csv<T: Display>(inner: T) -> EscapeCsv<T> {
+    EscapeCsv { inner }
+}
+
+/// Display wrapper that escapes its contents as a CSV field. Created with
+/// [`escape_csv`].
+#[derive(Debug, Clone, Copy)]
+pub struct EscapeCsv<T> {
+    inner: T,
+}
+
+impl<T: Display> Display for EscapeCsv<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut scanner = CsvScanner {
+            needs_quotes: false,
+        };
+
+        match fmt::Write::write_fmt(&mut scanner, format_args!("{}", self.inner)) {
+            Ok(()) => fmt::Write::write_fmt(f, format_args!("{}", self.inner)),
+            Err(fmt::Error) if scanner.needs_quotes => {
+                f.write_str("\"")?;
+                let mut dest = QuoteDoubler { dest: &mut *f };
+                fmt::Write::write_fmt(&mut dest, format_args!("{}", self.inner))?;
+                f.write_str("\"")
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/**
+Convert the ASCII letters in the output of `inner` to upper case. Other
+chars, including non-ASCII letters, are left unchanged: full Unicode case
+conversion can change the number of chars (`ß` becomes `SS`), and isn't
+supported.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::to_upper;
+
+let method = "get";
+let line = lazy_format!("{} /index.html", to_upper(method));
+
+assert_eq!(line.to_string(), "GET /index.html");
+```
+*/
+#[inline]
+pub fn to_upper<T: Display>(inner: T) -> ToUpper<T> {
+    ToUpper { inner }
+}
+
+/// Display wrapper that converts the ASCII letters in its contents to upper
+/// case. Created with [`to_upper`].
+#[derive(Debug, Clone, Copy)]
+pub struct ToUpper<T> {
+    inner: T,
+}
+
+impl<T: Display> Display for ToUpper<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut dest = AsciiCaseConverter { dest: f, upper: true };
+        fmt::Write::write_fmt(&mut dest, format_args!("{}", self.inner))
+    }
+}
+
+/**
+Convert the ASCII letters in the output of `inner` to lower case. Like with
+[`to_upper`], other chars are left unchanged.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::to_lower;
+
+let header = "Content-Type";
+assert_eq!(lazy_format!("{}: text/html", to_lower(header)).to_string(), "content-type: text/html");
+```
+*/
+#[inline]
+pub fn to_lower<T: Display>(inner: T) -> ToLower<T> {
+    ToLower { inner }
+}
+
+/// Display wrapper that converts the ASCII letters in its contents to lower
+/// case. Created with [`to_lower`].
+#[derive(Debug, Clone, Copy)]
+pub struct ToLower<T> {
+    inner: T,
+}
+
+impl<T: Display> Display for ToLower<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut dest = AsciiCaseConverter { dest: f, upper: false };
+        fmt::Write::write_fmt(&mut dest, format_args!("{}", self.inner))
+    }
+}
+
+/// How to align the contents of a [`column`][column()] within its width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Align {
+    /// Write the contents first, then the padding.
+    Left,
+    /// Write the padding first, then the contents.
+    Right,
+    /// Split the padding evenly around the contents. If it doesn't divide
+    /// evenly, the extra space goes after the contents.
+    Center,
+}
+
+/**
+Pad the output of `inner` with spaces (or another fill char, with
+[`with_fill`][Column::with_fill]) to at least `width` chars, aligned
+according to `align`. Unlike `{:>10}`, the width and alignment are chosen
+in code, rather than by the format string, which is useful for tables whose
+column widths are computed at runtime. Output that's already at least
+`width` chars long is written unchanged.
+
+This doesn't allocate. Left aligned columns are streamed, and padded
+afterwards; right aligned and centered columns format `inner` twice, once to
+measure its length, and again to write it.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::{column, Align};
+
+let widths = [6, 5];
+let (name, count) = ("apple", 3);
+
+let row = lazy_format!(
+    "|{}|{}|",
+    column(name, widths[0], Align::Left),
+    column(lazy_format!("{count}"), widths[1], Align::Right),
+);
+assert_eq!(row.to_string(), "|apple |    3|");
+assert_eq!(column("ab", 6, Align::Center).to_string(), "  ab  ");
+```
+*/
+#[inline]
+pub fn column<T: Display>(inner: T, width: usize, align: Align) -> Column<T> {
+    Column {
+        inner,
+        width,
+        align,
+        fill: ' ',
+    }
+}
+
+/// Display wrapper that pads its contents to a fixed width. Created with
+/// [`column`][column()].
+#[derive(Debug, Clone, Copy)]
+pub struct Column<T> {
+    inner: T,
+    width: usize,
+    align: Align,
+    fill: char,
+}
+
+impl<T> Column<T> {
+    /**
+    Pad with `fill` instead of spaces.
+
+    # Example
+
+    ```
+    use lazy_format::transform::{column, Align};
+
+    assert_eq!(column("Total", 10, Align::Left).with_fill('.').to_string(), "Total.....");
+    ```
+    */
+    #[inline]
+    #[must_use]
+    pub fn with_fill(self, fill: char) -> Self {
+        Self { fill, ..self }
+    }
+
+    fn write_fill(&self, dest: &mut Formatter<'_>, count: usize) -> fmt::Result {
+        match self.fill {
+            ' ' => write_spaces(dest, count),
+            fill => (0..count).try_for_each(|_| fmt::Write::write_char(dest, fill)),
+        }
+    }
+}
+
+impl<T: Display> Display for Column<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.align == Align::Left {
+            let mut dest = CharTally { dest: &mut *f, len: 0 };
+            fmt::Write::write_fmt(&mut dest, format_args!("{}", self.inner))?;
+            let len = dest.len;
+            return self.write_fill(f, self.width.saturating_sub(len));
+        }
+
+        let mut counter = CharCounter { len: 0 };
+        fmt::Write::write_fmt(&mut counter, format_args!("{}", self.inner))?;
+        let padding = self.width.saturating_sub(counter.len);
+
+        let before = match self.align {
+            Align::Center => padding / 2,
+            _ => padding,
+        };
+
+        self.write_fill(f, before)?;
+        fmt::Write::write_fmt(f, format_args!("{}", self.inner))?;
+        self.write_fill(f, padding - before)
+    }
+}
+
+/**
+Apply the width, fill, alignment, and precision of the formatter to the
+output of `inner`, like they'd be applied to a string. Lazy formats
+generally ignore these, because they're written directly into the
+destination in several pieces; to pad them, `padded` renders `inner` into a
+temporary [`String`] every time it's formatted. This is the only allocation
+it performs. If rendering `inner` fails, the error is returned and nothing
+is written. Requires the `alloc` feature.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::padded;
+
+let items = [1, 2, 3];
+let list = padded(lazy_format!("{item}" sep ", " for item in items));
+
+assert_eq!(format!("[{:^11}]", list), "[  1, 2, 3  ]");
+assert_eq!(format!("[{:*<9.4}]", list), "[1, 2*****]");
+```
+
+[`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+*/
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn padded<T: Display>(inner: T) -> Padded<T> {
+    Padded { inner }
+}
+
+/// Display wrapper that renders its contents to a string, so that they can
+/// be padded. Created with [`padded`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy)]
+pub struct Padded<T> {
+    inner: T,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Display> Display for Padded<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut rendered = String::new();
+        fmt::Write::write_fmt(&mut rendered, format_args!("{}", self.inner))?;
+        f.pad(&rendered)
+    }
+}
+
+/**
+Render `inner` only once. The first time the wrapper is formatted, `inner` is
+rendered to a [`String`], which is cached and written again on every later
+render, so any side effects of rendering `inner` happen at most once. If
+rendering `inner` fails, nothing is cached, and it'll be rendered again next
+time. Like [`padded`], the rendered string is written with the padding and
+precision of the destination.
+
+This costs an allocation for the cached string, and since the cache is
+stored in a [`OnceCell`], the wrapper isn't [`Sync`]. To evaluate some
+expression once per render, rather than once ever, use `once` in
+[`lazy_format!`][crate::lazy_format]. Requires the `alloc` feature.
+
+# Example
+
+```
+use std::cell::Cell;
+use lazy_format::lazy_format;
+use lazy_format::transform::render_once;
+
+let calls = Cell::new(0);
+let next_id = || {
+    calls.set(calls.get() + 1);
+    calls.get()
+};
+
+let id = render_once(lazy_format!("id-{}", next_id()));
+
+assert_eq!(id.to_string(), "id-1");
+assert_eq!(format!("[{:>5}]", id), "[ id-1]");
+assert_eq!(calls.get(), 1);
+```
+
+[`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+[`OnceCell`]: https://doc.rust-lang.org/std/cell/struct.OnceCell.html
+[`Sync`]: https://doc.rust-lang.org/std/marker/trait.Sync.html
+*/
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn render_once<T: Display>(inner: T) -> RenderOnce<T> {
+    RenderOnce {
+        inner,
+        rendered: OnceCell::new(),
+    }
+}
+
+/// Display wrapper that renders its contents once, and caches the result.
+/// Created with [`render_once`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct RenderOnce<T> {
+    inner: T,
+    rendered: OnceCell<String>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Display> Display for RenderOnce<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rendered = match self.rendered.get() {
+            Some(rendered) => rendered,
+            None => {
+                let mut rendered = String::new();
+                fmt::Write::write_fmt(&mut rendered, format_args!("{}", self.inner))?;
+                self.rendered.get_or_init(|| rendered)
+            }
+        };
+
+        f.pad(rendered)
+    }
+}
+
+/**
+Remove the leading whitespace that's common to every line of the output of
+`inner`, like Python's `textwrap.dedent`. This is useful for multi-line
+templates written as indented string literals, so that they can be indented
+to match the surrounding code.
+
+The common indentation isn't known until every line has been seen, so
+`inner` is first rendered to a [`String`]. Lines that are empty or contain
+only whitespace are ignored when finding the common indentation, and are
+written as empty lines. Tabs and spaces are compared exactly, so a line
+indented with a tab and a line indented with spaces have no indentation in
+common. Use [`indent`] to indent the result again. If rendering `inner`
+fails, the error is returned and nothing is written. Requires the `alloc`
+feature.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::dedent;
+
+let name = "world";
+let template = lazy_format!(
+    "
+    fn main() {{
+        println!(\"Hello, {name}!\");
+    }}
+    "
+);
+
+assert_eq!(
+    dedent(template).to_string(),
+    "\nfn main() {\n    println!(\"Hello, world!\");\n}\n",
+);
+```
+
+[`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+*/
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn dedent<T: Display>(inner: T) -> Dedent<T> {
+    Dedent { inner }
+}
+
+/// Display wrapper that removes the common indentation from each line of
+/// its contents. Created with [`dedent`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy)]
+pub struct Dedent<T> {
+    inner: T,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Display> Display for Dedent<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        /// Split a line into its content, without the line ending, and the
+        /// length of its indentation.
+        fn split_line(line: &str) -> (&str, usize) {
+            let content = line.trim_end_matches(['\r', '\n']);
+            let indent = content.len() - content.trim_start_matches([' ', '\t']).len();
+            (content, indent)
+        }
+
+        let mut rendered = String::new();
+        fmt::Write::write_fmt(&mut rendered, format_args!("{}", self.inner))?;
+
+        let common = rendered
+            .split_inclusive('\n')
+            .map(split_line)
+            .filter(|&(content, indent)| indent < content.len())
+            .map(|(content, indent)| &content[..indent])
+            .reduce(|common, indent| {
+                let shared = common
+                    .bytes()
+                    .zip(indent.bytes())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                &common[..shared]
+            })
+            .map_or(0, str::len);
+
+        rendered.split_inclusive('\n').try_for_each(|line| {
+            let (content, indent) = split_line(line);
+            let ending = &line[content.len()..];
+
+            if indent < content.len() {
+                f.write_str(&content[common..])?;
+            }
+
+            f.write_str(ending)
+        })
+    }
+}
+
+/**
+Format the value in an [`Option`], or `fallback` if it's [`None`]. This is
+the functional version of the `option` form of
+[`lazy_format!`][crate::lazy_format], for when the [`Option`] is built
+somewhere else. The formatter is passed through as-is, so any flags (like
+width or precision) apply to whichever value is written.
+
+# Example
+
+```
+use lazy_format::lazy_format;
+use lazy_format::transform::opt_display;
+
+fn find(id: u32) -> Option<impl std::fmt::Display> {
+    (id < 10).then(|| lazy_format!("user #{id}"))
+}
+
+assert_eq!(opt_display(find(3), "unknown").to_string(), "user #3");
+assert_eq!(opt_display(find(30), "unknown").to_string(), "unknown");
+assert_eq!(format!("[{:>8}]", opt_display(None::<u32>, "-")), "[       -]");
+```
+
+[`Option`]: https://doc.rust-lang.org/std/option/enum.Option.html
+[`None`]: https://doc.rust-lang.org/std/option/enum.Option.html#variant.None
+*/
+#[inline]
+pub fn opt_display<T: Display, D: Display>(value: Option<T>, fallback: D) -> OptDisplay<T, D> {
+    OptDisplay { value, fallback }
+}
+
+/// Display wrapper that writes an optional value, or a fallback. Created with
+/// [`opt_display`].
+#[derive(Debug, Clone, Copy)]
+pub struct OptDisplay<T, D> {
+    value: Option<T>,
+    fallback: D,
+}
+
+impl<T: Display, D: Display> Display for OptDisplay<T, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.value {
+            Some(ref value) => value.fmt(f),
+            None => self.fallback.fmt(f),
+        }
+    }
+}
+
+/**
+The formatting flags to render a value with, for use with [`with_flags`].
+The default has no flags set, like `{}`.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Flags {
+    /// The `#` flag, for the alternate form.
+    pub alternate: bool,
+
+    /// The `+` flag, to always write the sign of numbers.
+    pub sign_plus: bool,
+
+    /// The `0` flag, to pad numbers with zeroes after the sign.
+    pub zero_pad: bool,
+
+    /// The minimum width.
+    pub width: Option<usize>,
+
+    /// The precision.
+    pub precision: Option<usize>,
+}
+
+/**
+Format `inner` with a specific set of [`Flags`], instead of the flags of the
+destination. This can be used to force the alternate form of a value, or
+to give a value a width or precision that's only known at runtime.
+
+The flags of the destination are ignored entirely, rather than merged with
+the given ones. The fill and alignment can't be set this way, since they
+must be known at compile time; the default fill (a space) and the value's
+default alignment are always used.
+
+# Example
+
+```
+use lazy_format::make_lazy_format;
+use lazy_format::transform::{with_flags, Flags};
+
+let point = make_lazy_format!(|f| match f.alternate() {
+    false => write!(f, "(1, 2)"),
+    true => write!(f, "Point {{ x: 1, y: 2 }}"),
+});
+let verbose = Flags {
+    alternate: true,
+    ..Flags::default()
+};
+
+assert_eq!(format!("{}", with_flags(&point, verbose)), "Point { x: 1, y: 2 }");
+assert_eq!(format!("{:#}", with_flags(&point, Flags::default())), "(1, 2)");
+
+let digits = 3;
+let rounded = Flags {
+    sign_plus: true,
+    precision: Some(digits),
+    ..Flags::default()
+};
+assert_eq!(with_flags(2.0, rounded).to_string(), "+2.000");
+
+let padded = Flags {
+    zero_pad: true,
+    width: Some(4),
+    ..Flags::default()
+};
+assert_eq!(format!("{:>8}", with_flags(-7, padded)), "-007");
+```
+*/
+#[inline]
+pub fn with_flags<T: Display>(inner: T, flags: Flags) -> WithFlags<T> {
+    WithFlags { inner, flags }
+}
+
+/// Display wrapper that formats its contents with a specific set of flags.
+/// Created with [`with_flags`].
+#[derive(Debug, Clone, Copy)]
+pub struct WithFlags<T> {
+    inner: T,
+    flags: Flags,
+}
+
+impl<T: Display> Display for WithFlags<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let inner = &self.inner;
+
+        // Each combination of flags needs its own format string, since only
+        // the width and precision can be given at runtime.
+        macro_rules! write_with {
+            ($($flag:literal)*) => {
+                match (self.flags.width, self.flags.precision) {
+                    (None, None) => f.write_fmt(format_args!(
+                        concat!("{inner:", $($flag,)* "}"),
+                        inner = inner,
+                    )),
+                    (Some(width), None) => f.write_fmt(format_args!(
+                        concat!("{inner:", $($flag,)* "width$}"),
+                        inner = inner,
+                        width = width,
+                    )),
+                    (None, Some(precision)) => f.write_fmt(format_args!(
+                        concat!("{inner:", $($flag,)* ".precision$}"),
+                        inner = inner,
+                        precision = precision,
+                    )),
+                    (Some(width), Some(precision)) => f.write_fmt(format_args!(
+                        concat!("{inner:", $($flag,)* "width$.precision$}"),
+                        inner = inner,
+                        width = width,
+                        precision = precision,
+                    )),
+                }
+            };
+        }
+
+        match (self.flags.sign_plus, self.flags.alternate, self.flags.zero_pad) {
+            (false, false, false) => write_with!(),
+            (false, false, true) => write_with!("0"),
+            (false, true, false) => write_with!("#"),
+            (false, true, true) => write_with!("#" "0"),
+            (true, false, false) => write_with!("+"),
+            (true, false, true) => write_with!("+" "0"),
+            (true, true, false) => write_with!("+" "#"),
+            (true, true, true) => write_with!("+" "#" "0"),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Display, Formatter};
+
+/**
+A lazy format whose formatting closure can fail with a custom error type.
+Because [`Display`] can only return [`fmt::Error`], the custom error is
+stored in the `TryFormat` when formatting fails, and can be retrieved
+afterwards with [`take_error`][TryFormat::take_error]. Usually created with
+[`make_lazy_try_format!`][crate::make_lazy_try_format].
+*/
+pub struct TryFormat<F, E> {
+    write: F,
+    error: Cell<Option<E>>,
+}
+
+impl<F, E> TryFormat<F, E>
+where
+    F: Fn(&mut Formatter<'_>) -> Result<(), E>,
+{
+    /// Create a new `TryFormat` from a fallible formatting closure.
+    #[inline]
+    #[must_use]
+    pub fn new(write: F) -> Self {
+        Self {
+            write,
+            error: Cell::new(None),
+        }
+    }
+}
+
+/**
+Create a [`TryFormat`] from a fallible formatting closure. This is the
+function form of [`make_lazy_try_format!`][crate::make_lazy_try_format],
+for when the closure is built somewhere else, or to wrap a closure that
+borrows, rather than moves, its captures. When the closure fails, its error
+is stored, and the [`Display`] implementation returns a [`fmt::Error`]; the
+original error can then be retrieved with
+[`take_error`][TryFormat::take_error].
+
+# Example
+
+```
+use std::fmt::{self, Write};
+use lazy_format::fallible;
+
+#[derive(Debug, PartialEq)]
+enum RenderError {
+    Fmt,
+    Missing(&'static str),
+}
+
+impl From<fmt::Error> for RenderError {
+    fn from(_: fmt::Error) -> Self {
+        RenderError::Fmt
+    }
+}
+
+let lookup = |key: &'static str| match key {
+    "name" => Ok("Ferris"),
+    _ => Err(RenderError::Missing(key)),
+};
+
+let greeting = fallible(|f| -> Result<(), RenderError> {
+    write!(f, "Hello, {}! ", lookup("name")?)?;
+    write!(f, "You are {}.", lookup("age")?)?;
+    Ok(())
+});
+
+let mut dest = String::new();
+assert!(write!(&mut dest, "{}", greeting).is_err());
+assert_eq!(greeting.take_error(), Some(RenderError::Missing("age")));
+```
+*/
+#[inline]
+pub fn fallible<F, E>(write: F) -> TryFormat<F, E>
+where
+    F: Fn(&mut Formatter<'_>) -> Result<(), E>,
+{
+    TryFormat::new(write)
+}
+
+impl<F, E> TryFormat<F, E> {
+    /**
+    Take the error from the most recent failed render, if any. This returns
+    `None` if the value hasn't been formatted yet, if the most recent render
+    succeeded, or if the error was already taken.
+    */
+    #[inline]
+    #[must_use]
+    pub fn take_error(&self) -> Option<E> {
+        self.error.take()
+    }
+}
+
+impl<F, E> Display for TryFormat<F, E>
+where
+    F: Fn(&mut Formatter<'_>) -> Result<(), E>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.write)(f) {
+            Ok(()) => {
+                self.error.set(None);
+                Ok(())
+            }
+            Err(err) => {
+                self.error.set(Some(err));
+                Err(fmt::Error)
+            }
+        }
+    }
+}
+
+impl<F, E> Debug for TryFormat<F, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TryFormat").finish_non_exhaustive()
+    }
+}
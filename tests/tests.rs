@@ -35,6 +35,172 @@ impl ValueEmitter {
     }
 }
 
+mod make_lazy_format {
+    use std::fmt::{Debug, Display, UpperHex};
+
+    use lazy_format::make_lazy_format;
+
+    #[test]
+    fn borrowing_closure() {
+        let mut names = vec!["a".to_owned(), "b".to_owned()];
+
+        {
+            let result = make_lazy_format!(ref |f| write!(f, "{}", names.join(",")));
+            assert_eq!(result.to_string(), "a,b");
+            assert!(format!("{:?}", result).starts_with("make_lazy_format!(ref |"));
+        }
+
+        // `names` was only borrowed
+        names.push("c".to_owned());
+        assert_eq!(names.len(), 3);
+    }
+
+    #[test]
+    fn move_closure() {
+        fn owned(names: Vec<String>) -> impl Display + UpperHex + Debug {
+            make_lazy_format!(
+                move |f| write!(f, "{}", names.join(",")),
+                UpperHex => |f| write!(f, "{}", names.len()),
+            )
+        }
+
+        let result = owned(vec!["a".to_owned(), "b".to_owned()]);
+        assert_eq!(result.to_string(), "a,b");
+        assert_eq!(format!("{:X}", result), "2");
+        assert!(format!("{:?}", result).starts_with("make_lazy_format!(move |"));
+
+        // Closures are `move` closures by default
+        fn implicit(name: String) -> impl Display + Debug {
+            make_lazy_format!(|f| write!(f, "{}", name))
+        }
+
+        let result = implicit("a".to_owned());
+        assert_eq!(result.to_string(), "a");
+        assert!(format!("{:?}", result).starts_with("make_lazy_format!(|"));
+    }
+
+    #[test]
+    fn extra_traits() {
+        let value = 255u8;
+        let ratio = 0.00125;
+
+        let result = make_lazy_format!(
+            |f| write!(f, "{value}:{ratio}"),
+            LowerHex => |f| write!(f, "{value:x}:{ratio}"),
+            LowerExp => |f| write!(f, "{value}:{ratio:e}"),
+            UpperExp => |f| write!(f, "{value}:{ratio:E}"),
+        );
+
+        assert_eq!(format!("{}", result), "255:0.00125");
+        assert_eq!(format!("{:x}", result), "ff:0.00125");
+        assert_eq!(format!("{:e}", result), "255:1.25e-3");
+        assert_eq!(format!("{:E}", result), "255:1.25E-3");
+        assert_eq!(format!("{:#?}", result), "255:0.00125");
+    }
+
+    #[test]
+    fn by_reference() {
+        fn takes_display(value: impl Display) -> String {
+            format!("<{}>", value)
+        }
+
+        let value = 10;
+        let result = make_lazy_format!(|f| write!(f, "{}", value));
+        let by_ref = &result;
+
+        assert_eq!(format!("{}", format_args!("{} {}", &result, by_ref)), "10 10");
+        assert_eq!(takes_display(by_ref), "<10>");
+
+        let dynamic: [&dyn Display; 2] = [&result, by_ref];
+        assert_eq!(format!("{}{}", dynamic[0], dynamic[1]), "1010");
+
+        let mut dest = String::new();
+        std::fmt::Write::write_fmt(&mut dest, format_args!("{:>4}", by_ref)).unwrap();
+        assert_eq!(dest, "10");
+    }
+
+    #[test]
+    fn unit_block_bodies() {
+        let data = [1, 2, 3];
+
+        let looped = make_lazy_format!(|f| {
+            for value in &data {
+                write!(f, "{};", value)?;
+            }
+        });
+        assert_eq!(looped.to_string(), "1;2;3;");
+
+        let branched = make_lazy_format!(|f| {
+            if data.is_empty() {
+                f.write_str("empty")?
+            } else {
+                f.write_str("full")?
+            }
+        });
+        assert_eq!(branched.to_string(), "full");
+
+        let empty = make_lazy_format!(|_f| {});
+        assert_eq!(empty.to_string(), "");
+
+        let hex = make_lazy_format!(
+            |f| f.write_str("three"),
+            UpperHex => |f| {
+                write!(f, "{:X}", data.len())?;
+            },
+        );
+        assert_eq!(format!("{} {:X}", hex, hex), "three 3");
+    }
+
+    #[test]
+    fn result_block_bodies() {
+        let data = [1, 2, 3];
+
+        let explicit = make_lazy_format!(|f| {
+            write!(f, "{}", data[0])?;
+            Ok(())
+        });
+        assert_eq!(explicit.to_string(), "1");
+
+        let early_return = make_lazy_format!(|f| {
+            if data.len() > 2 {
+                return f.write_str("long");
+            }
+            f.write_str("short")
+        });
+        assert_eq!(early_return.to_string(), "long");
+
+        let failing = make_lazy_format!(|f| {
+            f.write_str("partial")?;
+            Err(std::fmt::Error)
+        });
+        let mut dest = String::new();
+        assert!(std::fmt::Write::write_fmt(&mut dest, format_args!("{}", failing)).is_err());
+    }
+
+    #[test]
+    fn references_as_dyn() {
+        fn render_all(items: &[&dyn Display]) -> String {
+            items.iter().map(|item| item.to_string()).collect()
+        }
+
+        fn debug_all<T: Debug>(items: &[T]) -> String {
+            items.iter().map(|item| format!("{:#?}", item)).collect()
+        }
+
+        let formats: Vec<_> = (1..=3)
+            .map(|i| make_lazy_format!(move |f| write!(f, "[{}]", i)))
+            .collect();
+
+        let refs: Vec<&dyn Display> = formats.iter().map(|lazy| lazy as &dyn Display).collect();
+        assert_eq!(render_all(&refs), "[1][2][3]");
+
+        let double_refs: Vec<&_> = formats.iter().collect();
+        let dynamic: [&dyn Display; 2] = [&double_refs[0], &&formats[2]];
+        assert_eq!(render_all(&dynamic), "[1][3]");
+        assert_eq!(debug_all(&double_refs), "[1][2][3]");
+    }
+}
+
 mod lazy_format {
     use std::fmt::Display;
     use std::fmt::Write;
@@ -60,12 +226,256 @@ mod lazy_format {
         assert_eq!(result, "Hello, World!");
     }
 
+    #[test]
+    fn precision_truncates() {
+        let emitter = &ValueEmitter::new();
+        let name = "αβγδε";
+
+        let result = lazy_format!("{}", name);
+        assert_eq!(format!("{:.3}", result), "αβγ");
+        assert_eq!(format!("{:.20}", result), "αβγδε");
+        assert_eq!(format!("{:.0}", result), "");
+        assert_eq!(format!("{}", result), "αβγδε");
+
+        let result = lazy_format!("{}", ref name);
+        assert_eq!(format!("{:.2}", result), "αβ");
+
+        let result = lazy_format!("{}", emitter.next());
+        assert_eq!(format!("{:.0}", result), "");
+        assert_eq!(format!("{}", result), "2");
+
+        let result = lazy_format!(once value = name; "{}", value);
+        assert_eq!(format!("{:.2}", result), "αβ");
+    }
+
+    #[test]
+    fn precision_ignored() {
+        let name = "αβγδε";
+
+        // Only `"{}"` with a single value is truncated
+        let result = lazy_format!("{}-{}", name, 1);
+        assert_eq!(format!("{:.3}", result), "αβγδε-1");
+
+        let result = lazy_format!("{name}!");
+        assert_eq!(format!("{:.2}", result), "αβγδε!");
+
+        let result = lazy_format!("{item}" for item in [name, name]);
+        assert_eq!(format!("{:.2}", result), "αβγδεαβγδε");
+    }
+
+    #[test]
+    fn borrowed_args() {
+        let big = vec![1, 2, 3];
+        let label = String::from("sum");
+        let small = 10;
+
+        let result = lazy_format!("{}: {} {:?} {total}", ref label, small, ref big, total = ref small);
+        assert_eq!(result.to_string(), "sum: 10 [1, 2, 3] 10");
+
+        // `big` and `label` were only borrowed
+        assert_eq!(big.len(), 3);
+        assert_eq!(label, "sum");
+        assert_eq!(result.to_string(), "sum: 10 [1, 2, 3] 10");
+
+        let other = vec![4, 5];
+        let result = lazy_format!("{:?}, {:?}", ref other, ref other);
+        assert_eq!(result.to_string(), "[4, 5], [4, 5]");
+        assert_eq!(other, [4, 5]);
+    }
+
+    #[test]
+    fn reference_args_are_moved() {
+        // Without `ref`, a reference argument is evaluated inside the lazy
+        // format, so the variable it refers to is moved in
+        fn describe(label: String) -> impl Display {
+            lazy_format!("{}: {len}", &label, len = &label.len())
+        }
+
+        assert_eq!(describe("abc".to_owned()).to_string(), "abc: 3");
+    }
+
+    #[test]
+    fn borrowed_field_args() {
+        struct Point {
+            name: String,
+            coords: (i32, (i32, String)),
+        }
+
+        let point = Point {
+            name: "origin".to_owned(),
+            coords: (0, (1, "up".to_owned())),
+        };
+
+        let result = lazy_format!(
+            "{} {} {} {dir}",
+            ref point.name,
+            ref point.coords.0,
+            ref point.coords.1 .0,
+            dir = ref point.coords.1 .1,
+        );
+        assert_eq!(result.to_string(), "origin 0 1 up");
+
+        // The fields of `point` were only borrowed
+        assert_eq!(point.name, "origin");
+        assert_eq!(point.coords.1 .1, "up");
+        assert_eq!(result.to_string(), "origin 0 1 up");
+    }
+
+    #[test]
+    fn methods_borrowing_self() {
+        struct Inventory {
+            owner: String,
+            items: Vec<(String, u32)>,
+            history: Vec<String>,
+        }
+
+        impl Inventory {
+            fn summary(&self) -> impl Display + '_ {
+                lazy_format!(
+                    ("{name} x{count}") wrap ("{}: ", self.owner) "" sep ", "
+                    for (name, count) in &self.items
+                )
+            }
+
+            fn add(&mut self, name: &str, count: u32) {
+                let entry = lazy_format!("{} added {count} {name}", ref self.owner);
+                self.history.push(entry.to_string());
+                self.items.push((name.to_owned(), count));
+            }
+        }
+
+        let mut inventory = Inventory {
+            owner: "Ferris".to_owned(),
+            items: Vec::new(),
+            history: Vec::new(),
+        };
+
+        inventory.add("apple", 2);
+        inventory.add("pear", 1);
+
+        let summary = inventory.summary();
+        assert_eq!(summary.to_string(), "Ferris: apple x2, pear x1");
+        assert_eq!(summary.to_string(), "Ferris: apple x2, pear x1");
+        assert_eq!(
+            inventory.history,
+            ["Ferris added 2 apple", "Ferris added 1 pear"]
+        );
+    }
+
+    #[test]
+    fn methods_consuming_self() {
+        struct Label {
+            name: String,
+            tags: Vec<&'static str>,
+        }
+
+        impl Label {
+            // Without `ref`, `self` is moved into the lazy format
+            fn into_display(self) -> impl Display {
+                lazy_format!("{} {:?}", &self.name, &self.tags)
+            }
+        }
+
+        let label = Label {
+            name: "crab".to_owned(),
+            tags: vec!["rust"],
+        };
+
+        assert_eq!(label.into_display().to_string(), r#"crab ["rust"]"#);
+    }
+
     #[test]
     fn no_args_with_curlies() {
         let result = lazy_format!("{{ braces }}").to_string();
         assert_eq!(result, "{ braces }")
     }
 
+    #[test]
+    fn no_args_is_lazy_str() {
+        use std::borrow::Cow;
+
+        use lazy_format::LazyStr;
+
+        let result: LazyStr = lazy_format!("{{ braces }}");
+        assert!(matches!(result.as_cow(), Cow::Borrowed("{ braces }")));
+
+        let result: LazyStr = lazy_format!("");
+        assert!(matches!(result.as_cow(), Cow::Borrowed("")));
+    }
+
+    #[test]
+    fn lazy_str_as_str() {
+        use lazy_format::LazyStr;
+
+        let result: LazyStr = lazy_format!("Hello, {{World}}!");
+        assert_eq!(result.as_str(), "Hello, {World}!");
+        assert_eq!(std::mem::size_of::<LazyStr>(), std::mem::size_of::<&str>());
+    }
+
+    #[test]
+    fn lazy_str_debug() {
+        use lazy_format::LazyStr;
+
+        let result: LazyStr = lazy_format!("Say \"{{hi}}\"\tit's me");
+        assert_eq!(
+            format!("{:?}", result),
+            r#"lazy_format!("Say \"{{hi}}\"\tit's me")"#
+        );
+        assert_eq!(format!("{:#?}", result), "Say \"{hi}\"\tit's me");
+        assert_eq!(format!("{:?}", lazy_format!("")), r#"lazy_format!("")"#);
+    }
+
+    #[test]
+    fn lazy_str_conversions() {
+        use lazy_format::{LazyStr, LazyString};
+
+        let from_literal: LazyStr = "plain".into();
+        assert_eq!(from_literal, lazy_format!("plain"));
+
+        let owned: LazyString = format!("{}-{}", "dyn", 1).into();
+        assert_eq!(owned.as_str(), "dyn-1");
+        assert_eq!(format!("[{:^6.4}]", owned), "[ dyn- ]");
+        assert!(owned.ends_with("-1"));
+
+        let converted = LazyString::from(lazy_format!("{{literal}}"));
+        assert_eq!(converted.to_string(), "{literal}");
+        assert_eq!(converted.into_string(), "{literal}");
+    }
+
+    #[test]
+    fn lazy_str_as_ref() {
+        fn shout(s: impl AsRef<str>) -> String {
+            s.as_ref().to_uppercase()
+        }
+
+        fn first_word(s: &str) -> &str {
+            s.split(' ').next().unwrap_or("")
+        }
+
+        let result = lazy_format!("hello world");
+        assert_eq!(shout(result), "HELLO WORLD");
+        assert_eq!(first_word(&result), "hello");
+    }
+
+    #[test]
+    fn lazy_str_pads() {
+        let result = lazy_format!("abc");
+        assert_eq!(format!("{:>5}", result), "  abc");
+        assert_eq!(format!("{:-<5}", result), "abc--");
+        assert_eq!(format!("{:^7.2}", result), "  ab   ");
+    }
+
+    #[test]
+    fn implicit_capture_is_not_lazy_str() {
+        use std::borrow::Cow;
+
+        use lazy_format::LazyFormatExt;
+
+        let name = "World";
+        let result = lazy_format!("{{Hello}}, {name}!");
+        assert!(matches!(result.as_cow(), Cow::Owned(s) if s == "{Hello}, World!"));
+    }
+
     #[test]
     fn ensure_lazy() {
         let emitter = &ValueEmitter::new();
@@ -110,6 +520,59 @@ mod lazy_format {
         assert_eq!(emitter.count(), 16);
     }
 
+    #[test]
+    fn test_once() {
+        let emitter = &ValueEmitter::new();
+
+        let result = lazy_format!(once v = emitter.next(); "{v}/{v}/{}", v);
+        assert_eq!(emitter.count(), 0);
+
+        assert_eq!(result.to_string(), "1/1/1");
+        assert_eq!(result.to_string(), "2/2/2");
+        assert_eq!(emitter.count(), 2);
+    }
+
+    #[test]
+    fn test_once_chained() {
+        let emitter = &ValueEmitter::new();
+
+        let result = lazy_format!(
+            once a = emitter.next();
+            once (b, c) = (emitter.next(), a.len());
+            match (a.as_str()) {
+                "1" => ("{a} {b} {c}"),
+                _ => "other",
+            }
+        );
+
+        assert_eq!(result.to_string(), "1 2 1");
+        assert_eq!(result.to_string(), "other");
+        assert_eq!(emitter.count(), 4);
+    }
+
+    #[test]
+    fn test_once_owned_captures() {
+        fn labeled(label: String, values: Vec<i32>) -> impl Display {
+            lazy_format!(
+                once total = values.iter().sum::<i32>();
+                if total > 0 => ("{label}: {total} from {} values", values.len())
+                else => ("{label}: none")
+            )
+        }
+
+        let s = String::from("x");
+        let result = lazy_format!(once n = 1; "{n} {s}");
+        assert_eq!(result.to_string(), "1 x");
+
+        let suffix = String::from("!");
+        let items = vec![1, 2];
+        let looped = lazy_format!(once n = 10; ("{}{suffix}", v + n) sep " " for v in (&items));
+        assert_eq!(looped.to_string(), "11! 12!");
+
+        assert_eq!(labeled("sum".into(), vec![1, 2]).to_string(), "sum: 3 from 2 values");
+        assert_eq!(labeled("sum".into(), vec![]).to_string(), "sum: none");
+    }
+
     #[test]
     fn test_return_value() {
         let values = (0..5).map(|value| lazy_format!("'{}'... ", value));
@@ -153,90 +616,2803 @@ mod lazy_format {
     }
 
     #[test]
-    fn test_if_else() {
-        let value = 10;
-
-        // This mostly exists to use "expand macro recursively" to check that
-        // it correctly generates optimized write calls in these branches. A
-        // future version of this test can actually test this by using a Write
-        // adapter.
-        let result = lazy_format!(
-            if value == 10 => "ten"
-            else if value > 10 => ("value: {}", value)
-            else => "small"
-        );
+    fn test_match_binding_in_tuple_output() {
+        fn describe(value: i32) -> impl Display {
+            lazy_format!(match (value) {
+                0 => "zero",
+                small @ 1..=9 => ("small: {}", small),
+                value => ("other: {}", value),
+            })
+        }
 
-        assert_eq!(result.to_string(), "ten");
+        assert_eq!(describe(0).to_string(), "zero");
+        assert_eq!(describe(5).to_string(), "small: 5");
+        assert_eq!(describe(50).to_string(), "other: 50");
     }
 
     #[test]
-    fn test_if_without_else() {
-        let value = 0;
+    fn test_match_let_guards() {
+        fn lookup(key: u8) -> Option<&'static str> {
+            match key {
+                1 => Some("one"),
+                2 => Some("two"),
+                _ => None,
+            }
+        }
 
-        let result = lazy_format!(
-            if value == 10 => "10"
-            else if value > 10 => "value: {value}"
-        );
+        fn describe(key: u8, min_len: usize) -> impl Display {
+            lazy_format!(match (key) {
+                0 => "zero",
+                k if let Some(name) = lookup(k) && name.len() > min_len => ("{k}: {name}"),
+                k if let Some(name) = lookup(k) => ("{k}: short {}", name),
+                k if k % 2 == 0 => ("{k}: even"),
+                k => ("{}: unknown", k)
+            })
+        }
 
-        assert_eq!(result.to_string(), "");
+        assert_eq!(describe(0, 3).to_string(), "zero");
+        assert_eq!(describe(1, 2).to_string(), "1: one");
+        assert_eq!(describe(2, 3).to_string(), "2: short two");
+        assert_eq!(describe(4, 3).to_string(), "4: even");
+        assert_eq!(describe(5, 3).to_string(), "5: unknown");
     }
 
     #[test]
-    fn test_loop_underscore() {
-        let result = lazy_format!("Ab" for _ in 0..5);
-        assert_eq!(result.to_string(), "AbAbAbAbAb");
+    fn test_match_long_guard() {
+        fn describe(value: u32) -> impl Display {
+            lazy_format!(match (value) {
+                0 => "zero",
+                n if n != 1 && n != 2 && n != 3 && n != 4 && n != 5 && n != 6 && n != 7
+                    && n != 8 && n != 9 && n != 10 && n != 11 && n != 12 && n != 13 && n != 14
+                    && n != 15 && n != 16 && n != 17 && n != 18 && n != 19 && n != 20 && n != 21
+                    && n != 22 && n != 23 && n != 24 && n != 25 && n != 26 && n != 27 && n != 28
+                    && n != 29 && n != 30 && n != 31 && n != 32 && n != 33 && n != 34 && n != 35
+                    && n != 36 && n != 37 && n != 38 && n != 39 && n != 40 && n != 41 && n != 42
+                    && n != 43 && n != 44 && n != 45 && n != 46 && n != 47 && n != 48 && n != 49
+                    && n != 50 && n != 51 && n != 52 && n != 53 && n != 54 && n != 55 && n != 56
+                    && n != 57 && n != 58 && n != 59 && n != 60 && n != 61 && n != 62 && n != 63
+                    && n != 64 && n != 65 && n != 66 && n != 67 && n != 68 && n != 69 && n != 70
+                    => ("big: {}", n),
+                n => ("small: {}", n),
+            })
+        }
+
+        assert_eq!(describe(0).to_string(), "zero");
+        assert_eq!(describe(35).to_string(), "small: 35");
+        assert_eq!(describe(70).to_string(), "small: 70");
+        assert_eq!(describe(71).to_string(), "big: 71");
     }
 
     #[test]
-    fn test_contextual_loop() {
-        let value = 10;
-        let pairs = [('a', 'b'), ('c', 'd')];
-        let result = lazy_format!("{value} {left} {right}, " for &(left, right) in &pairs);
-        assert_eq!(result.to_string(), "10 a b, 10 c d, ")
+    fn test_match_default() {
+        fn describe(value: usize) -> impl Display {
+            lazy_format!(match (value) {
+                0 => "zero",
+                n if n % 2 == 0 => "even: {n}",
+                default => ("other: {}", value),
+            })
+        }
+
+        assert_eq!(describe(0).to_string(), "zero");
+        assert_eq!(describe(4).to_string(), "even: 4");
+        assert_eq!(describe(7).to_string(), "other: 7");
     }
 
-    /// Test that the for loop version of lazy_format still works when the
-    /// iterator type still has a try_for_each method, for some reason.
     #[test]
-    fn test_bad_iterator() {
-        #[derive(Copy, Clone)]
-        struct Collection<'a> {
-            slice: &'a [i32],
+    fn test_match_default_binding() {
+        // `default` is only a keyword in the last arm
+        fn describe(value: Option<&str>) -> impl Display + '_ {
+            lazy_format!(match (value) {
+                Some(default) => ("value: {}", default),
+                None => "none",
+            })
         }
 
-        impl<'a> Iterator for Collection<'a> {
-            type Item = &'a i32;
+        assert_eq!(describe(Some("abc")).to_string(), "value: abc");
+        assert_eq!(describe(None).to_string(), "none");
 
-            fn next(&mut self) -> Option<Self::Item> {
-                self.slice.split_first().map(|(head, tail)| {
-                    self.slice = tail;
-                    head
-                })
-            }
+        let value = 3;
+        let result = lazy_format!(match (value) {
+            0 => "zero",
+            default if default > 2 => ("big: {}", default),
+            default => "small",
+        });
+        assert_eq!(result.to_string(), "big: 3");
+    }
+
+    #[test]
+    fn test_match_many_arms() {
+        fn describe(value: u32) -> impl Display {
+            lazy_format!(match (value) {
+                0 => "0", 1 => "1", 2 => "2", 3 => "3", 4 => "4", 5 => "5", 6 => "6", 7 => "7", 8 => "8", 9 => "9",
+                10 => "10", 11 => "11", 12 => "12", 13 => "13", 14 => "14", 15 => "15", 16 => "16", 17 => "17", 18 => "18", 19 => "19",
+                20 => "20", 21 => "21", 22 => "22", 23 => "23", 24 => "24", 25 => "25", 26 => "26", 27 => "27", 28 => "28", 29 => "29",
+                30 => "30", 31 => "31", 32 => "32", 33 => "33", 34 => "34", 35 => "35", 36 => "36", 37 => "37", 38 => "38", 39 => "39",
+                40 => "40", 41 => "41", 42 => "42", 43 => "43", 44 => "44", 45 => "45", 46 => "46", 47 => "47", 48 => "48", 49 => "49",
+                50 => "50", 51 => "51", 52 => "52", 53 => "53", 54 => "54", 55 => "55", 56 => "56", 57 => "57", 58 => "58", 59 => "59",
+                60 => "60", 61 => "61", 62 => "62", 63 => "63", 64 => "64", 65 => "65", 66 => "66", 67 => "67", 68 => "68", 69 => "69",
+                70 => "70", 71 => "71", 72 => "72", 73 => "73", 74 => "74", 75 => "75", 76 => "76", 77 => "77", 78 => "78", 79 => "79",
+                80 => "80", 81 => "81", 82 => "82", 83 => "83", 84 => "84", 85 => "85", 86 => "86", 87 => "87", 88 => "88", 89 => "89",
+                90 => "90", 91 => "91", 92 => "92", 93 => "93", 94 => "94", 95 => "95", 96 => "96", 97 => "97", 98 => "98", 99 => "99",
+                100 => "100", 101 => "101", 102 => "102", 103 => "103", 104 => "104", 105 => "105", 106 => "106", 107 => "107", 108 => "108", 109 => "109",
+                110 => "110", 111 => "111", 112 => "112", 113 => "113", 114 => "114", 115 => "115", 116 => "116", 117 => "117", 118 => "118", 119 => "119",
+                120 => "120", 121 => "121", 122 => "122", 123 => "123", 124 => "124", 125 => "125", 126 => "126", 127 => "127", 128 => "128", 129 => "129",
+                130 => "130", 131 => "131", 132 => "132", 133 => "133", 134 => "134", 135 => "135", 136 => "136", 137 => "137", 138 => "138", 139 => "139",
+                140 => "140", 141 => "141", 142 => "142", 143 => "143", 144 => "144", 145 => "145", 146 => "146", 147 => "147", 148 => "148", 149 => "149",
+                default => "many",
+            })
         }
 
-        impl<'a> Collection<'a> {
-            pub fn new(slice: &'a [i32]) -> Self {
-                Self { slice }
-            }
+        assert_eq!(describe(0).to_string(), "0");
+        assert_eq!(describe(137).to_string(), "137");
+        assert_eq!(describe(149).to_string(), "149");
+        assert_eq!(describe(150).to_string(), "many");
+    }
 
-            #[allow(dead_code)]
-            pub fn try_for_each<T>(&mut self, _body: impl FnMut(&'a i32) -> T) -> T {
-                panic!("This shouldn't be called")
-            }
+    #[test]
+    fn test_match_implicit_default() {
+        fn describe(value: Option<u8>) -> impl Display {
+            lazy_format!(match? (value) {
+                Some(0) => "zero",
+                Some(n) if n > 100 => ("big: {}", n)
+            })
         }
 
-        let collection = Collection::new(&[1, 2, 3, 4]);
-        let output = lazy_format!("{item} " for item in collection);
-        assert_eq!(output.to_string(), "1 2 3 4 ");
+        assert_eq!(describe(Some(0)).to_string(), "zero");
+        assert_eq!(describe(Some(200)).to_string(), "big: 200");
+        assert_eq!(describe(Some(5)).to_string(), "");
+        assert_eq!(describe(None).to_string(), "");
+
+        // An already exhaustive match is fine too
+        let flag = true;
+        let result = lazy_format!(match? (flag) {
+            true => "yes",
+            false => "no",
+        });
+        assert_eq!(result.to_string(), "yes");
     }
 
     #[test]
-    fn test_lazy_format_with_possible_name_collision() {
-        const STYLE: i32 = 10;
+    fn test_if_let_binding_in_tuple_output() {
+        fn describe(value: Result<i32, &'static str>) -> impl Display {
+            lazy_format!(
+                if let Ok(0) = value => "zero"
+                else if let Ok(x) = value => ("ok: {}", x)
+                else if let Err(err) = value => ("error: {}", err)
+            )
+        }
 
-        let s = lazy_format!("Value: {STYLE}").to_string();
+        assert_eq!(describe(Ok(0)).to_string(), "zero");
+        assert_eq!(describe(Ok(3)).to_string(), "ok: 3");
+        assert_eq!(describe(Err("bad")).to_string(), "error: bad");
+    }
 
-        assert_eq!(s, "Value: 10");
+    #[test]
+    fn test_positional_args_in_outputs() {
+        let value = 3;
+        let option = Some(4);
+        let result: Result<i32, &str> = Err("bad");
+
+        let matched = lazy_format!(match (value) {
+            0 => "zero",
+            value => ("{0} and {0}", value),
+        });
+        assert_eq!(matched.to_string(), format!("{0} and {0}", value));
+
+        let if_else = lazy_format!(
+            if value > 0 => ("{1}{0}{1}", value, "|")
+            else => ("{0}", "negative")
+        );
+        assert_eq!(if_else.to_string(), format!("{1}{0}{1}", value, "|"));
+
+        let if_let = lazy_format!(if let Some(x) = option => ("{0}-{0:>2}", x) else => "none");
+        assert_eq!(if_let.to_string(), "4- 4");
+
+        let looped = lazy_format!(("{1}{0}", x, "#") sep " " for x in [1, 2]);
+        assert_eq!(looped.to_string(), "#1 #2");
+
+        let repeated = lazy_format!(("{0}{0}", value) repeat 2);
+        assert_eq!(repeated.to_string(), "3333");
+
+        // The value of an `option` or `result` is the first positional argument
+        let optional = lazy_format!(option option => ("{0}/{1}/{0}", "max") else "none");
+        assert_eq!(optional.to_string(), "4/max/4");
+
+        let fallible = lazy_format!(result result => "{0}" else ("{0} {0:?}"));
+        assert_eq!(fallible.to_string(), "bad \"bad\"");
+
+        let basic = lazy_format!("{0} {1} {0} {name}", value, 7, name = "n");
+        assert_eq!(basic.to_string(), format!("{0} {1} {0} {name}", value, 7, name = "n"));
+    }
+
+    #[test]
+    fn test_named_args_in_tuple_outputs() {
+        fn label(value: i32) -> &'static str {
+            if value < 0 {
+                "neg"
+            } else {
+                "pos"
+            }
+        }
+
+        let value = -3;
+        let option = Some(4);
+
+        let matched = lazy_format!(match (value) {
+            0 => ("{name}", name = "zero"),
+            n if n > 0 => ("{name}: {n}", name = label(n)),
+            n => ("{name}: {val}", name = label(n), val = n.abs()),
+        });
+        assert_eq!(matched.to_string(), "neg: 3");
+
+        let if_else = lazy_format!(
+            if value > 0 => ("{name}: {val}", name = label(value), val = value)
+            else => ("{name}", name = label(value))
+        );
+        assert_eq!(if_else.to_string(), "neg");
+
+        let if_let = lazy_format!(
+            if let Some(x) = option => ("{name}: {val}", name = label(x), val = x * 2)
+            else => "none"
+        );
+        assert_eq!(if_let.to_string(), "pos: 8");
+
+        let looped = lazy_format!(
+            ("{name}={val}", name = label(x), val = x.abs()) sep ", " for x in [1, -2]
+        );
+        assert_eq!(looped.to_string(), "pos=1, neg=2");
+
+        let repeated = lazy_format!(("{name}", name = label(value)) repeat 2);
+        assert_eq!(repeated.to_string(), "negneg");
+    }
+
+    #[test]
+    fn test_else_let_binding() {
+        fn describe(value: Option<i32>) -> impl Display {
+            lazy_format!(
+                if let Some(0) = value => "zero"
+                else let x = value.unwrap_or(20) => ("{}, {x}", x)
+            )
+        }
+
+        assert_eq!(describe(Some(0)).to_string(), "zero");
+        assert_eq!(describe(Some(10)).to_string(), "10, 10");
+        assert_eq!(describe(None).to_string(), "20, 20");
+    }
+
+    #[test]
+    fn test_option() {
+        let emitter = &ValueEmitter::new();
+        let value = Some(5);
+        let width = 4;
+
+        let result = lazy_format!(option value => "[{:>width$}]" else "[none]");
+        assert_eq!(result.to_string(), "[   5]");
+
+        let result = lazy_format!(option value.map(|v| v * 2) => ("{} {}", emitter.next()) else "");
+        assert_eq!(result.to_string(), "10 1");
+
+        let none: Option<i32> = None;
+        let result = lazy_format!(option none => "{}" else ("none {}", emitter.next()));
+        assert_eq!(result.to_string(), "none 2");
+    }
+
+    #[test]
+    fn test_option_as() {
+        let pair = Some((1, "one"));
+
+        let result = lazy_format!(option (pair) as (n, name) => ("{n}: {}", name) else "none");
+        assert_eq!(result.to_string(), "1: one");
+
+        let result = lazy_format!(option (pair.filter(|&(n, _)| n > 1)) as _ => "some" else "none");
+        assert_eq!(result.to_string(), "none");
+    }
+
+    #[test]
+    fn test_result() {
+        let emitter = &ValueEmitter::new();
+        let width = 3;
+
+        let ok: Result<i32, &str> = Ok(5);
+        let result = lazy_format!(result ok => ("[{:>width$}] {}", emitter.next()) else "{}");
+        assert_eq!(result.to_string(), "[  5] 1");
+
+        let err: Result<i32, &str> = Err("bad");
+        let result = lazy_format!(result err => "{}" else ("error: {} {}", emitter.next()));
+        assert_eq!(result.to_string(), "error: bad 2");
+    }
+
+    #[test]
+    fn test_result_as() {
+        fn describe(value: Result<(i32, i32), String>) -> impl Display {
+            lazy_format!(
+                result (value.as_ref()) as &(a, b) => ("{a} + {b} = {}", a + b)
+                else message => ("failed: {}", message.to_uppercase())
+            )
+        }
+
+        assert_eq!(describe(Ok((1, 2))).to_string(), "1 + 2 = 3");
+        assert_eq!(describe(Err("oops".to_owned())).to_string(), "failed: OOPS");
+    }
+
+    #[test]
+    fn test_match_empty() {
+        enum Void {}
+
+        fn by_value(value: Void) -> impl Display {
+            lazy_format!(match (value) {})
+        }
+
+        fn by_ref(value: &Void) -> impl Display + '_ {
+            lazy_format!(match (*value) {})
+        }
+
+        fn generic<T: Display>(value: Result<T, Void>) -> impl Display {
+            lazy_format!(match (value) {
+                Ok(ref value) => "ok: {value}",
+            })
+        }
+
+        // The empty matches can't be called, but they have to compile
+        let _ = by_value;
+        let _ = by_ref;
+
+        assert_eq!(generic::<i32>(Ok(1)).to_string(), "ok: 1");
+        assert_eq!(
+            lazy_format!(match? (Option::<Void>::None) {}).to_string(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_match_display_arms() {
+        fn describe(value: Option<i32>, verbose: bool) -> impl Display {
+            let inner = lazy_format!("inner {}", value.unwrap_or(0));
+
+            lazy_format!(match (value) {
+                None => "nothing",
+                Some(0) => @ lazy_format!("zero ({})", if verbose { "verbose" } else { "quiet" }),
+                Some(n) if n < 0 => @ -n,
+                Some(1) => @ inner,
+                default => @ lazy_format!(match (verbose) {
+                    true => @ inner,
+                    false => "other",
+                }),
+            })
+        }
+
+        assert_eq!(describe(None, true).to_string(), "nothing");
+        assert_eq!(describe(Some(0), true).to_string(), "zero (verbose)");
+        assert_eq!(describe(Some(-5), true).to_string(), "5");
+        assert_eq!(describe(Some(1), false).to_string(), "inner 1");
+        assert_eq!(describe(Some(2), true).to_string(), "inner 2");
+        assert_eq!(describe(Some(2), false).to_string(), "other");
+
+        // The formatter is passed along unchanged
+        let value = 7;
+        let padded = lazy_format!(match? (value) { 7 => @ value });
+        assert_eq!(format!("[{:>3}]", padded), "[  7]");
+    }
+
+    #[test]
+    fn test_match_tuple() {
+        fn describe(name: &str, count: usize, verbose: bool) -> String {
+            lazy_format!(match (name, count, verbose) {
+                (_, 0, _) => "nothing",
+                ("", 1, _) | (_, 1, false) => "one",
+                (name, 1, true) => "one {name}",
+                (name, n, true) if n > 10 => ("lots of {}s", name),
+                default => "{count}",
+            })
+            .to_string()
+        }
+
+        assert_eq!(describe("cat", 0, true), "nothing");
+        assert_eq!(describe("", 1, true), "one");
+        assert_eq!(describe("cat", 1, false), "one");
+        assert_eq!(describe("cat", 1, true), "one cat");
+        assert_eq!(describe("cat", 11, true), "lots of cats");
+        assert_eq!(describe("cat", 11, false), "11");
+
+        let (a, b) = (1, 2);
+        let result = lazy_format!(match? (a, b) {
+            (1, 2) => "matched",
+        });
+        assert_eq!(result.to_string(), "matched");
+        assert_eq!(lazy_format!(match? (b, a) { (1, 2) => "matched" }).to_string(), "");
+    }
+
+    #[test]
+    fn test_pad() {
+        let width = 8;
+        let value = 3.5;
+
+        assert_eq!(lazy_format!(pad value, width, '.').to_string(), "3.5.....");
+        assert_eq!(lazy_format!(pad "name", width, ' ').to_string(), "name    ");
+        assert_eq!(lazy_format!(pad "日本", 4, '＊').to_string(), "日本＊＊");
+        assert_eq!(lazy_format!(pad "too long", 3, '-').to_string(), "too long");
+
+        let inner = lazy_format!("{value}" sep "+" for value in [1, 2]);
+        let padded = lazy_format!(pad inner, width, '-');
+        assert_eq!(padded.to_string(), "1+2-----");
+        assert_eq!(lazy_format!("[{padded}]").to_string(), "[1+2-----]");
+    }
+
+    #[test]
+    fn test_show() {
+        let emitter = &ValueEmitter::new();
+        let count = 3;
+
+        let result = lazy_format!(show count > 2 => ("on {}", emitter.next()) else "off");
+        assert_eq!(result.to_string(), "on 1");
+
+        let result = lazy_format!(show count % 2 == 0 => "even" else ("odd: {count}"));
+        assert_eq!(result.to_string(), "odd: 3");
+        assert_eq!(emitter.count(), 1);
+    }
+
+    #[test]
+    fn test_show_chain() {
+        fn describe(n: i32) -> impl Display {
+            lazy_format!(
+                show n < 0 => ("negative {}", -n)
+                else show n == 0 => "zero"
+                else show n % 2 == 0 => "even {n}"
+                else ("odd {}", n)
+            )
+        }
+
+        assert_eq!(describe(-3).to_string(), "negative 3");
+        assert_eq!(describe(0).to_string(), "zero");
+        assert_eq!(describe(4).to_string(), "even 4");
+        assert_eq!(describe(5).to_string(), "odd 5");
+    }
+
+    #[test]
+    fn test_if_else() {
+        let value = 10;
+
+        // This mostly exists to use "expand macro recursively" to check that
+        // it correctly generates optimized write calls in these branches. A
+        // future version of this test can actually test this by using a Write
+        // adapter.
+        let result = lazy_format!(
+            if value == 10 => "ten"
+            else if value > 10 => ("value: {}", value)
+            else => "small"
+        );
+
+        assert_eq!(result.to_string(), "ten");
+    }
+
+    #[test]
+    fn test_if_without_else() {
+        let value = 0;
+
+        let result = lazy_format!(
+            if value == 10 => "10"
+            else if value > 10 => "value: {value}"
+        );
+
+        assert_eq!(result.to_string(), "");
+    }
+
+    #[test]
+    fn test_loop_underscore() {
+        let result = lazy_format!("Ab" for _ in 0..5);
+        assert_eq!(result.to_string(), "AbAbAbAbAb");
+    }
+
+    #[test]
+    fn test_repeat() {
+        let emitter = &ValueEmitter::new();
+        let result = lazy_format!(("{}-", emitter.next()) repeat 3);
+        assert_eq!(result.to_string(), "1-2-3-");
+        assert_eq!(result.to_string(), "4-5-6-");
+    }
+
+    #[test]
+    fn test_repeat_count_is_lazy() {
+        use std::cell::Cell;
+
+        let count = Cell::new(2);
+        let count = &count;
+        let result = lazy_format!("ab" repeat count.get());
+        assert_eq!(result.to_string(), "abab");
+
+        count.set(0);
+        assert_eq!(result.to_string(), "");
+    }
+
+    #[test]
+    fn test_fallible_loop() {
+        let values = ["1", "2", "x", "4"];
+        let parsed = || values.iter().map(|s| s.parse::<i32>());
+
+        let mut dest = String::new();
+        write!(&mut dest, "{}", lazy_format!("{v}, " for v in try parsed())).unwrap_err();
+        assert_eq!(dest, "1, 2, ");
+
+        let result = lazy_format!(("{}, ", v * 2) for v in try parsed().take(2));
+        assert_eq!(result.to_string(), "2, 4, ");
+    }
+
+    #[test]
+    fn test_loop_separator() {
+        let values = [1, 2, 3];
+        let sep = "; ";
+
+        assert_eq!(lazy_format!("{v}" sep ", " for v in values).to_string(), "1, 2, 3");
+        assert_eq!(lazy_format!("{v}" sep ("{sep}") for v in [1]).to_string(), "1");
+        assert_eq!(lazy_format!("{v}" sep ", " for v in 0..0).to_string(), "");
+        assert_eq!(
+            lazy_format!(("<{}>", v * 2) sep ("{}", sep) for v in values).to_string(),
+            "<2>; <4>; <6>"
+        );
+    }
+
+    #[test]
+    fn test_loop_wrap() {
+        let values = [1, 2, 3];
+        let name = "values";
+
+        assert_eq!(lazy_format!("{v} " wrap "(" ")" for v in values).to_string(), "(1 2 3 )");
+        assert_eq!(
+            lazy_format!("{v}" wrap ("{name}: [") "]" sep ", " for &v in &values).to_string(),
+            "values: [1, 2, 3]"
+        );
+        assert_eq!(lazy_format!("{v}" wrap "[" "]" sep ", " for v in 0..0).to_string(), "[]");
+    }
+
+    /// Test that loops re-render the same way each time, since the
+    /// collection expression is evaluated for each render
+    #[test]
+    fn test_loop_rerender() {
+        let values = vec![1, 2, 3];
+        let values_ref = &values;
+
+        let by_ref = lazy_format!("{v} " for v in values_ref);
+        let adapter = lazy_format!("{v} " for v in values_ref.iter().map(|v| v * 10));
+        let range = lazy_format!("{v} " for v in 1..4);
+
+        for _ in 0..2 {
+            assert_eq!(by_ref.to_string(), "1 2 3 ");
+            assert_eq!(adapter.to_string(), "10 20 30 ");
+            assert_eq!(range.to_string(), "1 2 3 ");
+        }
+    }
+
+    #[test]
+    fn test_loop_generator() {
+        use std::cell::Cell;
+        use std::iter;
+
+        // State created in the collection expression starts over each render
+        let fresh = lazy_format!("{v}" sep "," for v in {
+            let mut count = 0;
+            iter::from_fn(move || {
+                count += 1;
+                (count <= 3).then_some(count * 10)
+            })
+        });
+
+        assert_eq!(fresh.to_string(), "10,20,30");
+        assert_eq!(fresh.to_string(), "10,20,30");
+
+        // State shared from outside continues, and runs out
+        let counter = Cell::new(0);
+        let counter = &counter;
+        let shared = lazy_format!("{v}" sep "," for v in iter::from_fn(|| {
+            counter.set(counter.get() + 1);
+            (counter.get() <= 4).then_some(counter.get())
+        }).take(2));
+
+        assert_eq!(shared.to_string(), "1,2");
+        assert_eq!(shared.to_string(), "3,4");
+        assert_eq!(shared.to_string(), "");
+
+        // Unless it's collected up front
+        let counter = Cell::new(0);
+        let counter = &counter;
+        let collected = lazy_format!("{v}" sep "," for v in collect iter::from_fn(|| {
+            counter.set(counter.get() + 1);
+            (counter.get() <= 2).then_some(counter.get())
+        }));
+
+        assert_eq!(collected.to_string(), "1,2");
+        assert_eq!(collected.to_string(), "1,2");
+    }
+
+    #[test]
+    fn test_loop_collect() {
+        use std::cell::Cell;
+
+        let pulled = Cell::new(0);
+        let pulled = &pulled;
+        let words = vec!["a".to_owned(), "bb".to_owned()];
+
+        let result = lazy_format!(
+            ("{}:{}", word, word.len())
+            wrap "[" "]" sep ", "
+            for word in collect words.into_iter().inspect(|_| pulled.set(pulled.get() + 1))
+        );
+
+        // The iterator is consumed right away, and only once
+        assert_eq!(pulled.get(), 2);
+        assert_eq!(result.to_string(), "[a:1, bb:2]");
+        assert_eq!(result.to_string(), "[a:1, bb:2]");
+        assert_eq!(pulled.get(), 2);
+
+        let empty = lazy_format!("{v}" wrap "[" "]" for v in collect 0..0);
+        assert_eq!(empty.to_string(), "[]");
+    }
+
+    #[test]
+    fn test_loop_take() {
+        let values = [1, 2, 3, 4, 5];
+        let more = 2;
+
+        assert_eq!(lazy_format!("{v} " for v in values take 3).to_string(), "1 2 3 ");
+        assert_eq!(
+            lazy_format!("{v}" sep ", " for v in values take 3 else ", ...").to_string(),
+            "1, 2, 3, ..."
+        );
+        assert_eq!(
+            lazy_format!("{v}" sep ", " for v in values take 5 else ", ...").to_string(),
+            "1, 2, 3, 4, 5"
+        );
+        assert_eq!(
+            lazy_format!("{v}" wrap "[" "]" sep ", " for v in (1..) take 2 else (", and {} more", more))
+                .to_string(),
+            "[1, 2, and 2 more]"
+        );
+        assert_eq!(
+            lazy_format!("{v} " for v in (0..0) take 0 else "more").to_string(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_loop_take_stops_early() {
+        use std::cell::Cell;
+
+        let pulled = Cell::new(0);
+        let pulled = &pulled;
+        let counted = lazy_format!("{v}" for v in ((0..).inspect(|_| pulled.set(pulled.get() + 1))) take 3 else "+");
+
+        assert_eq!(counted.to_string(), "012+");
+        // 3 items, plus one more to check for overflow
+        assert_eq!(pulled.get(), 4);
+    }
+
+    #[test]
+    fn test_loop_filter() {
+        let values = [1, 2, 3, 4, 5, 6];
+
+        assert_eq!(lazy_format!("{v} " for v in values if v % 2 == 0).to_string(), "2 4 6 ");
+        assert_eq!(
+            lazy_format!("{v}" wrap "[" "]" sep ", " for v in values if v % 2 == 1).to_string(),
+            "[1, 3, 5]"
+        );
+        assert_eq!(
+            lazy_format!("{v}" wrap "[" "]" sep ", " for v in values if v > 10).to_string(),
+            "[]"
+        );
+        assert_eq!(
+            lazy_format!(("{}={}", k, v) sep "&" for (k, v) in (values.iter().enumerate()) if k >= 4)
+                .to_string(),
+            "4=5&5=6"
+        );
+    }
+
+    #[test]
+    fn test_loop_filter_writes_nothing() {
+        let values = [1, 2, 3, 4, 5];
+
+        assert_eq!(lazy_format!("{v} " for v in values if v > 5).to_string(), "");
+        assert_eq!(
+            lazy_format!("{v}" sep ", " for v in values if v == 1 || v == 5).to_string(),
+            "1, 5"
+        );
+        assert_eq!(
+            lazy_format!("{v}" wrap "(" ")" sep " " for v in values if v % 2 == 0).to_string(),
+            "(2 4)"
+        );
+        assert_eq!(
+            lazy_format!(("{}", "") sep "," for _v in values if true).to_string(),
+            ",,,,"
+        );
+    }
+
+    #[test]
+    fn test_conditional_loop_arms() {
+        fn listing(items: &[i32], verbose: bool) -> impl Display + '_ {
+            lazy_format!(
+                if verbose => ("{v}" wrap "[" "]" sep ", " for v in items)
+                else => ("({} items)", items.len())
+            )
+        }
+
+        assert_eq!(listing(&[1, 2, 3], true).to_string(), "[1, 2, 3]");
+        assert_eq!(listing(&[1, 2, 3], false).to_string(), "(3 items)");
+        assert_eq!(listing(&[], true).to_string(), "[]");
+
+        let words = ["a", "bb", "ccc"];
+        let matched = |limit: usize| {
+            lazy_format!(match (limit) {
+                0 => "none",
+                1 => ("{}", words[0]),
+                n => (("{w}") sep "+" for w in words.iter().take(n)),
+            })
+        };
+        assert_eq!(matched(0).to_string(), "none");
+        assert_eq!(matched(1).to_string(), "a");
+        assert_eq!(matched(2).to_string(), "a+bb");
+
+        let maybe = Some(2);
+        let repeated = lazy_format!(
+            if let Some(n) = maybe => ("ab" for _ in 0..n)
+            else => "-"
+        );
+        assert_eq!(repeated.to_string(), "abab");
+    }
+
+    #[test]
+    fn test_nested_loop_output() {
+        let grid = vec![vec![1, 2, 3], vec![], vec![4]];
+        let grid = &grid;
+
+        let rows = lazy_format!(("{cell}" sep " " for cell in row) wrap "<" ">" sep "|" for row in grid);
+        assert_eq!(rows.to_string(), "<1 2 3||4>");
+
+        let filtered = lazy_format!(
+            (("{}", cell * 10) for cell in row) sep ";" for row in grid if !row.is_empty()
+        );
+        assert_eq!(filtered.to_string(), "102030;40");
+    }
+
+    #[test]
+    fn test_loop_zip() {
+        let labels = vec!["a".to_owned(), "b".to_owned(), "c".to_owned()];
+        let labels = &labels;
+        let values = [1, 2];
+
+        assert_eq!(
+            lazy_format!("{l}={v}" sep ", " for (l, v) in zip labels, values).to_string(),
+            "a=1, b=2"
+        );
+        assert_eq!(
+            lazy_format!(("{}{} ", l, v * 10) for (l, &v) in zip values.iter().map(|v| v + 1), &values)
+                .to_string(),
+            "210 320 "
+        );
+        assert_eq!(
+            lazy_format!("{v}" wrap "[" "]" for (_, v) in zip 0..0, labels).to_string(),
+            "[]"
+        );
+    }
+
+    #[test]
+    fn test_loop_chunks() {
+        fn hex_dump(data: Vec<u8>) -> impl Display {
+            lazy_format!(
+                ("{}\n", lazy_format!("{byte:02x}" sep " " for byte in chunk))
+                for chunk in chunks data, 16
+            )
+        }
+
+        let dump = hex_dump((0..40).collect());
+        let expected = "\
+            00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f\n\
+            10 11 12 13 14 15 16 17 18 19 1a 1b 1c 1d 1e 1f\n\
+            20 21 22 23 24 25 26 27\n";
+        assert_eq!(dump.to_string(), expected);
+        assert_eq!(dump.to_string(), expected);
+        assert_eq!(hex_dump(Vec::new()).to_string(), "");
+
+        let words = ["a", "b", "c", "d", "e"];
+        assert_eq!(
+            lazy_format!(("{:?}", chunk) sep " " for chunk in chunks words, 2).to_string(),
+            r#"["a", "b"] ["c", "d"] ["e"]"#
+        );
+        assert_eq!(
+            lazy_format!(("{}", chunk.len()) sep "|" for chunk in chunks &words[..4], 3).to_string(),
+            "3|1"
+        );
+    }
+
+    #[test]
+    fn test_loop_step() {
+        let every_third = lazy_format!("{v}" sep "," for v in (0..10) step 3);
+        assert_eq!(every_third.to_string(), "0,3,6,9");
+
+        let partial = lazy_format!("{v}" sep "," for v in (0..9) step 3);
+        assert_eq!(partial.to_string(), "0,3,6");
+
+        let list = vec!["a", "b", "c", "d"];
+        let list_ref = &list;
+        let every_other = lazy_format!("{v}" wrap "[" "]" for v in list_ref step 2);
+        assert_eq!(every_other.to_string(), "[ac]");
+
+        let all = lazy_format!("{v}" for v in list_ref step 1);
+        assert_eq!(all.to_string(), "abcd");
+
+        let empty = lazy_format!("{v}" wrap "<" ">" for v in (0..0) step 3);
+        assert_eq!(empty.to_string(), "<>");
+    }
+
+    #[test]
+    fn test_loop_step_evaluated_once() {
+        let emitter = ValueEmitter::new();
+        let emitter = &emitter;
+        let next_step = || emitter.next().len() + 1;
+
+        let sampled = lazy_format!("{v}" for v in (0..6) step next_step());
+        assert_eq!(emitter.count(), 0);
+        assert_eq!(sampled.to_string(), "024");
+        assert_eq!(emitter.count(), 1);
+        assert_eq!(sampled.to_string(), "024");
+        assert_eq!(emitter.count(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "step != 0")]
+    fn test_loop_step_zero() {
+        let broken = lazy_format!("{v}" for v in (0..3) step 0);
+        let _ = broken.to_string();
+    }
+
+    #[test]
+    fn test_loop_rev() {
+        let history = vec!["open".to_owned(), "edit".to_owned(), "save".to_owned()];
+        let history_ref = &history;
+
+        let newest_first = lazy_format!("{action}" sep ", " for action in rev history_ref);
+        assert_eq!(newest_first.to_string(), "save, edit, open");
+
+        let owned = lazy_format!("{v}" wrap "[" "]" sep " " for v in rev vec![1, 2, 3]);
+        assert_eq!(owned.to_string(), "[3 2 1]");
+        assert_eq!(owned.to_string(), "[3 2 1]");
+
+        let range = lazy_format!("{n} " for n in rev 0..4);
+        assert_eq!(range.to_string(), "3 2 1 0 ");
+
+        let empty = lazy_format!("{n}" wrap "<" ">" for n in rev 0..0);
+        assert_eq!(empty.to_string(), "<>");
+
+        let pairs = lazy_format!(("{}={}", k, v) sep "&" for (k, v) in rev [("a", 1), ("b", 2)]);
+        assert_eq!(pairs.to_string(), "b=2&a=1");
+    }
+
+    #[test]
+    fn test_loop_flatten() {
+        fn owned(nested: Vec<Vec<i32>>) -> impl Display {
+            lazy_format!("{v}" wrap "[" "]" sep " " for v in flatten &nested)
+        }
+
+        let result = owned(vec![vec![1, 2], vec![], vec![3, 4, 5]]);
+        assert_eq!(result.to_string(), "[1 2 3 4 5]");
+        assert_eq!(result.to_string(), "[1 2 3 4 5]");
+
+        let empty: Vec<Vec<i32>> = vec![vec![], vec![]];
+        let empty = &empty;
+        assert_eq!(lazy_format!("{v}" for v in flatten empty).to_string(), "");
+
+        let options = [Some(1), None, Some(3)];
+        assert_eq!(
+            lazy_format!(("{}", v * 2) for v in flatten options).to_string(),
+            "26"
+        );
+    }
+
+    #[test]
+    fn test_loop_enumerate() {
+        use std::cell::Cell;
+
+        let values = ["a", "b", "c"];
+
+        assert_eq!(
+            lazy_format!("{i}{v}" sep " " for (i, v) in enumerate values.iter().rev()).to_string(),
+            "0c 1b 2a"
+        );
+        assert_eq!(
+            lazy_format!("{i}{v}" sep " " for (i, v) in enumerate values from 1).to_string(),
+            "1a 2b 3c"
+        );
+
+        let starts = Cell::new(0);
+        let starts = &starts;
+        let numbered = lazy_format!(
+            ("{}{} ", i, v) for (i, v) in enumerate (values.iter().skip(1)) from {
+                starts.set(starts.get() + 1);
+                10 * starts.get()
+            }
+        );
+
+        assert_eq!(numbered.to_string(), "10b 11c ");
+        assert_eq!(numbered.to_string(), "20b 21c ");
+        assert_eq!(starts.get(), 2);
+    }
+
+    #[test]
+    fn test_loop_rename() {
+        let values = [1, 2, 3];
+        let values_ref = &values;
+
+        assert_eq!(
+            lazy_format!("{value}" wrap "[" "]" sep ", " for v in values as value).to_string(),
+            "[1, 2, 3]"
+        );
+        assert_eq!(
+            lazy_format!(("{}-", item * 2) for x in (values_ref.iter()) as item).to_string(),
+            "2-4-6-"
+        );
+    }
+
+    #[test]
+    fn test_loop_rename_keeps_binding() {
+        let words = vec![String::from("one"), String::from("three")];
+
+        assert_eq!(
+            lazy_format!(("{word}:{}", w.len()) sep " " for w in (&words) as word).to_string(),
+            "one:3 three:5"
+        );
+    }
+
+    #[test]
+    fn test_join() {
+        use std::fmt::Display;
+
+        let inner = lazy_format!("{}+{}", 1, 2);
+        let owned = String::from("owned");
+        let items: &[&dyn Display] = &[&10, &"str", &owned, &inner, &'c'];
+        let sep = " | ";
+
+        assert_eq!(lazy_format!(sep ", " in items).to_string(), "10, str, owned, 1+2, c");
+        assert_eq!(
+            lazy_format!(sep ("{sep}") in &items[..2]).to_string(),
+            "10 | str"
+        );
+
+        let empty: &[&dyn Display] = &[];
+        assert_eq!(lazy_format!(sep ", " in empty).to_string(), "");
+        assert_eq!(lazy_format!(sep ", " in [1, 2, 3]).to_string(), "1, 2, 3");
+    }
+
+    #[test]
+    fn test_loop_filter_moves_captures() {
+        fn above(values: Vec<usize>, threshold: usize) -> impl Display + 'static {
+            lazy_format!("{v}" sep " " for v in (values.iter()) if *v > threshold)
+        }
+
+        let result = above(vec![5, 10, 15, 20], 10);
+        assert_eq!(result.to_string(), "15 20");
+        assert_eq!(result.to_string(), "15 20");
+    }
+
+    #[test]
+    fn test_contextual_loop() {
+        let value = 10;
+        let pairs = [('a', 'b'), ('c', 'd')];
+        let result = lazy_format!("{value} {left} {right}, " for &(left, right) in &pairs);
+        assert_eq!(result.to_string(), "10 a b, 10 c d, ")
+    }
+
+    /// Test that the for loop version of lazy_format still works when the
+    /// iterator type still has a try_for_each method, for some reason.
+    #[test]
+    fn test_loop_collections() {
+        use std::collections::HashMap;
+
+        fn owned_vec(values: Vec<String>) -> impl Display + 'static {
+            lazy_format!("{v} " for v in &values)
+        }
+
+        let result = owned_vec(vec!["a".to_owned(), "b".to_owned()]);
+        assert_eq!(result.to_string(), "a b ");
+        assert_eq!(result.to_string(), "a b ");
+
+        let array = [1, 2, 3];
+        assert_eq!(lazy_format!("{v} " for v in array).to_string(), "1 2 3 ");
+        assert_eq!(lazy_format!("{v} " for v in &array).to_string(), "1 2 3 ");
+
+        let slice: &[i32] = &array[1..];
+        assert_eq!(lazy_format!("{v} " for v in slice).to_string(), "2 3 ");
+
+        let map = HashMap::from([("key", 10)]);
+        let map_ref = &map;
+        assert_eq!(lazy_format!("{k}={v} " for (k, v) in map_ref).to_string(), "key=10 ");
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_loop_map() {
+        use std::collections::{BTreeMap, HashMap};
+
+        fn render(map: &BTreeMap<String, u32>) -> impl Display + '_ {
+            lazy_format!("{k}={v}" sep ", " for (k, v) in map)
+        }
+
+        let mut map = BTreeMap::new();
+        map.insert("b".to_owned(), 2);
+        map.insert("a".to_owned(), 1);
+
+        let result = render(&map);
+        assert_eq!(result.to_string(), "a=1, b=2");
+
+        let map = HashMap::from([("x", 1), ("y", 2), ("z", 3)]);
+        let map_ref = &map;
+        let result = lazy_format!(("{}={} ", k, v) for (k, v) in map_ref).to_string();
+        let mut pairs: Vec<&str> = result.split_whitespace().collect();
+        pairs.sort_unstable();
+        assert_eq!(pairs, ["x=1", "y=2", "z=3"]);
+    }
+
+    #[test]
+    fn test_bad_iterator() {
+        #[derive(Copy, Clone)]
+        struct Collection<'a> {
+            slice: &'a [i32],
+        }
+
+        impl<'a> Iterator for Collection<'a> {
+            type Item = &'a i32;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.slice.split_first().map(|(head, tail)| {
+                    self.slice = tail;
+                    head
+                })
+            }
+        }
+
+        impl<'a> Collection<'a> {
+            pub fn new(slice: &'a [i32]) -> Self {
+                Self { slice }
+            }
+
+            #[allow(dead_code)]
+            pub fn try_for_each<T>(&mut self, _body: impl FnMut(&'a i32) -> T) -> T {
+                panic!("This shouldn't be called")
+            }
+        }
+
+        let collection = Collection::new(&[1, 2, 3, 4]);
+        let output = lazy_format!("{item} " for item in collection);
+        assert_eq!(output.to_string(), "1 2 3 4 ");
+    }
+
+    #[test]
+    fn test_debug() {
+        let emitter = &ValueEmitter::new();
+        let result = lazy_format!("value: {}", emitter.next());
+
+        let debug = format!("{:?}", result);
+        assert!(debug.starts_with("make_lazy_format!("));
+        assert_eq!(emitter.count(), 0);
+
+        assert_eq!(format!("{:#?}", result), "value: 1");
+        assert_eq!(emitter.count(), 1);
+    }
+
+    #[test]
+    fn test_rendered_ord() {
+        let mut lines: Vec<_> = [3, 10, 2, 1]
+            .into_iter()
+            .map(|n| lazy_format!("item {n}"))
+            .collect();
+
+        lines.sort();
+
+        let sorted: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(sorted, ["item 1", "item 10", "item 2", "item 3"]);
+
+        assert!(lines[0] < lines[1]);
+        assert!(lines[1] != lines[2]);
+        assert_eq!(lines[3], lines[3].clone());
+    }
+
+    #[test]
+    fn test_rendered_ord_failing() {
+        use std::cmp::Ordering;
+        use std::fmt;
+
+        use lazy_format::{make_lazy_format, LazyFormatExt};
+
+        let failing = make_lazy_format!(|f| {
+            f.write_str("zzz")?;
+            Err(fmt::Error)
+        });
+
+        assert_eq!(failing.cmp_rendered(&"a"), Ordering::Less);
+        assert_eq!("a".cmp_rendered(&failing), Ordering::Greater);
+        assert_eq!(failing.cmp_rendered(&failing), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_lazy_format_with_possible_name_collision() {
+        const STYLE: i32 = 10;
+        const UNESCAPED: i32 = 20;
+
+        let s = lazy_format!("Value: {STYLE}").to_string();
+        assert_eq!(s, "Value: 10");
+
+        let s = lazy_format!("Value: {UNESCAPED}").to_string();
+        assert_eq!(s, "Value: 20");
+    }
+}
+
+mod assert_static {
+    use std::fmt::Display;
+    use std::thread;
+
+    use lazy_format::{assert_static, lazy_format, make_lazy_format};
+
+    #[test]
+    fn owned_captures() {
+        let names = vec!["a".to_owned(), "b".to_owned()];
+        let count = names.len();
+
+        let list = assert_static(lazy_format!("{name}" sep ", " for name in &names));
+        let summary = assert_static(lazy_format!("{count} names: {list}"));
+        let literal = assert_static(lazy_format!("constant"));
+        let closure = assert_static(make_lazy_format!(move |f| write!(f, "{}", count * 2)));
+
+        let stored: Vec<Box<dyn Display + Send>> =
+            vec![Box::new(summary), Box::new(literal), Box::new(closure)];
+
+        let rendered = thread::spawn(move || {
+            stored
+                .iter()
+                .map(|lazy| lazy.to_string())
+                .collect::<Vec<_>>()
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(rendered, ["2 names: a, b", "constant", "4"]);
+    }
+
+    #[test]
+    fn static_borrows() {
+        static NAMES: [&str; 2] = ["x", "y"];
+
+        let joined = assert_static(lazy_format!("{name}" for name in &NAMES));
+        assert_eq!(joined.to_string(), "xy");
+    }
+}
+
+mod count_renders {
+    use crate::ValueEmitter;
+    use lazy_format::{count_renders, lazy_format};
+
+    #[test]
+    fn counts_nested_renders() {
+        let emitter = &ValueEmitter::new();
+        let inner = &count_renders(lazy_format!("{}", emitter.next()));
+        let outer = lazy_format!("{inner}, {inner}");
+
+        assert_eq!(outer.to_string(), "1, 2");
+        assert_eq!(inner.render_count(), 2);
+        assert_eq!(emitter.count(), 2);
+
+        assert_eq!(outer.to_string(), "3, 4");
+        assert_eq!(inner.render_count(), 4);
+    }
+}
+
+mod debug {
+    use std::cell::Cell;
+    use std::collections::BTreeMap;
+    use std::fmt::Display;
+
+    use lazy_format::{lazy_debug, lazy_debug_list, lazy_debug_map, lazy_format};
+
+    #[derive(Debug, Clone)]
+    struct Node {
+        name: &'static str,
+        weights: Vec<(u8, Option<char>)>,
+        children: BTreeMap<u32, Vec<&'static str>>,
+    }
+
+    fn node() -> Node {
+        Node {
+            name: "root",
+            weights: vec![(1, Some('a')), (2, None)],
+            children: BTreeMap::from([(1, vec!["x", "y"]), (2, vec![])]),
+        }
+    }
+
+    #[test]
+    fn debug_list_matches_debug() {
+        let values = vec![Some(1), None, Some(3)];
+        let list = lazy_debug_list(&values);
+
+        assert_eq!(list.to_string(), format!("{:?}", values));
+        assert_eq!(format!("{:#}", list), format!("{:#?}", values));
+        assert_eq!(lazy_debug_list(0..0).to_string(), "[]");
+    }
+
+    #[test]
+    fn debug_list_is_lazy() {
+        let values = [1, 2, 3];
+        let list = lazy_debug_list(values.iter().map(|v| lazy_format!("<{v}>")).map(|v| v.to_string()));
+
+        assert_eq!(list.to_string(), r#"["<1>", "<2>", "<3>"]"#);
+        assert_eq!(list.to_string(), r#"["<1>", "<2>", "<3>"]"#);
+    }
+
+    #[test]
+    fn debug_map_matches_debug() {
+        let map = BTreeMap::from([("a", vec![1]), ("b", vec![])]);
+        let lazy = lazy_debug_map(&map);
+
+        assert_eq!(lazy.to_string(), format!("{:?}", map));
+        assert_eq!(format!("{:#}", lazy), format!("{:#?}", map));
+    }
+
+    #[test]
+    fn lazy_debug_matches_debug() {
+        let node = node();
+        let debug = lazy_debug!(ref node);
+
+        assert_eq!(debug.to_string(), format!("{:?}", node));
+        assert_eq!(format!("{:#}", debug), format!("{:#?}", node));
+        assert_eq!(format!("{:#?}", debug), format!("{:#?}", node));
+        assert_eq!(lazy_debug!("text").to_string(), r#""text""#);
+        assert_eq!(format!("{:>5}", lazy_debug!(42)), "   42");
+
+        // `node` was only borrowed
+        assert_eq!(node.name, "root");
+    }
+
+    #[test]
+    fn lazy_debug_nested_collections() {
+        let node = node();
+        let nested = vec![Some(vec![node.clone()]), None];
+        let debug = lazy_debug!(ref nested);
+
+        assert_eq!(debug.to_string(), format!("{:?}", nested));
+        assert_eq!(format!("{:#}", debug), format!("{:#?}", nested));
+
+        let fields = lazy_debug!(ref node.weights, ref node.children);
+        assert_eq!(
+            fields.to_string(),
+            format!("{:?}", (&node.weights, &node.children))
+        );
+        assert_eq!(
+            format!("{:#}", fields),
+            format!("{:#?}", (&node.weights, &node.children))
+        );
+    }
+
+    #[test]
+    fn lazy_debug_reference_args_are_moved() {
+        // Without `ref`, the variable behind a reference is moved in
+        fn describe(node: Node) -> impl Display {
+            lazy_debug!(&node.name, &node.weights)
+        }
+
+        let node = node();
+        let expected = format!("{:?}", (&node.name, &node.weights));
+        assert_eq!(describe(node).to_string(), expected);
+    }
+
+    #[test]
+    fn lazy_debug_is_lazy() {
+        let calls = Cell::new(0);
+        let next = || {
+            calls.set(calls.get() + 1);
+            vec![calls.get()]
+        };
+
+        let debug = lazy_debug!(next(), "fixed");
+        assert_eq!(calls.get(), 0);
+        assert_eq!(debug.to_string(), r#"([1], "fixed")"#);
+        assert_eq!(lazy_format!("<{debug}>").to_string(), r#"<([2], "fixed")>"#);
+        assert_eq!(calls.get(), 2);
+    }
+}
+
+mod list {
+    use std::collections::BTreeSet;
+
+    use lazy_format::{lazy_format, oxford_list};
+
+    #[test]
+    fn oxford_list_lengths() {
+        let list = |n: usize| oxford_list(1..=n, ", ", ", and ", " and ").to_string();
+
+        assert_eq!(list(0), "");
+        assert_eq!(list(1), "1");
+        assert_eq!(list(2), "1 and 2");
+        assert_eq!(list(3), "1, 2, and 3");
+        assert_eq!(list(5), "1, 2, 3, 4, and 5");
+    }
+
+    #[test]
+    fn oxford_list_items_and_separators() {
+        let names = BTreeSet::from(["Carol", "Alice", "Bob"]);
+        let names = oxford_list(&names, "; ", "; or ", " or ");
+        assert_eq!(names.to_string(), "Alice; Bob; or Carol");
+        assert_eq!(names.to_string(), "Alice; Bob; or Carol");
+
+        let prices = [1.5, 2.25];
+        let prices = prices.iter().map(|p| lazy_format!("${p:.2}"));
+        let formatted = oxford_list(prices, ", ", ", and ", " and ");
+        assert_eq!(lazy_format!("costs {formatted}").to_string(), "costs $1.50 and $2.25");
+    }
+}
+
+mod lazy {
+    use lazy_format::prelude::*;
+
+    #[test]
+    fn alias() {
+        let value = 3;
+        let items = [1, 2];
+
+        assert_eq!(lazy!("{value}").to_string(), "3");
+        assert_eq!(lazy!("{item} " for item in items).to_string(), "1 2 ");
+        assert_eq!(
+            lazy!(if value > 2 => "big" else => "small").to_string(),
+            "big"
+        );
+    }
+}
+
+mod lazy_args {
+    use std::fmt::Display;
+
+    use lazy_format::lazy_args;
+
+    #[test]
+    fn outlives_statement() {
+        let stored: Vec<Box<dyn Display>> = (0..3)
+            .map(|i| {
+                let label = format!("item-{i}");
+                Box::new(lazy_args!("{label}={}", i * 10)) as Box<dyn Display>
+            })
+            .collect();
+
+        let rendered: Vec<String> = stored.iter().map(|args| args.to_string()).collect();
+        assert_eq!(rendered, ["item-0=0", "item-1=10", "item-2=20"]);
+    }
+
+    #[test]
+    fn same_as_format_args() {
+        let width = 6;
+        let value = 2.5;
+
+        assert_eq!(
+            lazy_args!("[{:>width$.2}] {value:e} {0}", value).to_string(),
+            format!("{}", format_args!("[{:>width$.2}] {value:e} {0}", value))
+        );
+    }
+}
+
+mod make_lazy_try_format {
+    use std::fmt::{self, Write};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use lazy_format::{fallible, lazy_format, make_lazy_try_format};
+
+    #[derive(Debug, PartialEq)]
+    enum Error {
+        Fmt,
+        TooLong(usize),
+    }
+
+    impl From<fmt::Error> for Error {
+        fn from(_: fmt::Error) -> Self {
+            Error::Fmt
+        }
+    }
+
+    #[test]
+    fn success_clears_error() {
+        let limit = std::cell::Cell::new(3);
+        let limit = &limit;
+        let name = "Nathan";
+
+        let result = make_lazy_try_format!(|f| -> Result<(), Error> {
+            if name.len() > limit.get() {
+                return Err(Error::TooLong(name.len()));
+            }
+            write!(f, "Hello, {}", name)?;
+            Ok(())
+        });
+
+        assert_eq!(result.take_error(), None);
+
+        let mut dest = String::new();
+        assert!(write!(&mut dest, "{}", result).is_err());
+
+        limit.set(10);
+        assert_eq!(result.to_string(), "Hello, Nathan");
+        assert_eq!(result.take_error(), None);
+    }
+
+    #[test]
+    fn destination_error() {
+        struct BadDest;
+
+        impl Write for BadDest {
+            fn write_str(&mut self, _s: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        let result = make_lazy_try_format!(|f| -> Result<(), Error> {
+            f.write_str("content")?;
+            Ok(())
+        });
+
+        assert!(write!(BadDest, "{}", result).is_err());
+        assert_eq!(result.take_error(), Some(Error::Fmt));
+    }
+
+    #[test]
+    fn fallible_custom_error() {
+        #[derive(Debug, PartialEq)]
+        struct Unknown(u32);
+
+        let ids = [1, 2, 7];
+        let names = |id| match id {
+            1 => Ok("one"),
+            2 => Ok("two"),
+            id => Err(Unknown(id)),
+        };
+
+        let listing = fallible(|f| -> Result<(), Unknown> {
+            for &id in &ids {
+                let name = names(id)?;
+                f.write_str(name).map_err(|fmt::Error| Unknown(0))?;
+                f.write_str(" ").map_err(|fmt::Error| Unknown(0))?;
+            }
+            Ok(())
+        });
+
+        // `to_string` panics when `Display` fails, and the error is stored
+        let attempt = catch_unwind(AssertUnwindSafe(|| listing.to_string()));
+        assert!(attempt.is_err());
+        assert_eq!(listing.take_error(), Some(Unknown(7)));
+        assert_eq!(listing.take_error(), None);
+
+        let mut dest = String::new();
+        assert_eq!(write!(&mut dest, "<{}>", listing), Err(fmt::Error));
+        assert_eq!(dest, "<one two ");
+        assert_eq!(listing.take_error(), Some(Unknown(7)));
+    }
+
+    #[test]
+    fn fallible_borrows_and_recovers() {
+        let limit = std::cell::Cell::new(2);
+        let values = vec![1, 2, 3];
+
+        // The closure borrows `limit` and `values`, rather than moving them
+        let joined = fallible(|f| {
+            if values.len() > limit.get() {
+                return Err(Error::TooLong(values.len()));
+            }
+            let values = &values;
+            write!(f, "{}", lazy_format!("{v}" sep "," for v in values))?;
+            Ok(())
+        });
+
+        assert!(write!(String::new(), "{}", joined).is_err());
+        assert_eq!(joined.take_error(), Some(Error::TooLong(3)));
+
+        limit.set(3);
+        assert_eq!(joined.to_string(), "1,2,3");
+        assert_eq!(joined.take_error(), None);
+        assert_eq!(values.len(), 3);
+    }
+}
+
+mod lazy_concat {
+    use std::fmt::Display;
+
+    use crate::ValueEmitter;
+    use lazy_format::{lazy_concat, lazy_format};
+
+    #[test]
+    fn concat() {
+        let emitter = &ValueEmitter::new();
+        let a = lazy_format!("[{}]", emitter.next());
+        let result = lazy_concat!(a, " ", emitter.next(), " ", a);
+
+        assert_eq!(emitter.count(), 0);
+        assert_eq!(result.to_string(), "[1] 2 [3]");
+        assert_eq!(result.to_string(), "[4] 5 [6]");
+    }
+
+    #[test]
+    fn concat_empty() {
+        assert_eq!(lazy_concat!().to_string(), "");
+    }
+
+    #[test]
+    fn concat_ignores_outer_flags() {
+        let result = lazy_concat!(1, 2.5, "x");
+        assert_eq!(format!("{:>8}", result), "12.5x");
+    }
+
+    #[test]
+    fn concat_moves_captures() {
+        fn owned() -> impl Display + 'static {
+            let name = String::from("World");
+            lazy_concat!("Hello, ", name, "!")
+        }
+
+        assert_eq!(owned().to_string(), "Hello, World!");
+    }
+}
+
+mod lazy_map {
+    use std::cell::Cell;
+
+    use lazy_format::lazy_map;
+
+    #[test]
+    fn transform_runs_per_render() {
+        let calls = Cell::new(0);
+        let name = String::from("world");
+
+        let result = lazy_map!(
+            name,
+            |name: &String| {
+                calls.set(calls.get() + 1);
+                name.to_uppercase()
+            },
+            "Hello, {}{}",
+            "!"
+        );
+
+        assert_eq!(calls.get(), 0);
+        assert_eq!(result.to_string(), "Hello, WORLD!");
+        assert_eq!(result.to_string(), "Hello, WORLD!");
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn value_is_evaluated_once() {
+        let evaluated = Cell::new(0);
+
+        let result = lazy_map!(
+            {
+                evaluated.set(evaluated.get() + 1);
+                [1, 2, 3]
+            },
+            |values| values.iter().sum::<i32>(),
+            "{:03}",
+        );
+
+        assert_eq!(evaluated.get(), 1);
+        assert_eq!(result.to_string(), "006");
+        assert_eq!(result.to_string(), "006");
+        assert_eq!(evaluated.get(), 1);
+    }
+}
+
+mod lazy_cols {
+    use lazy_format::{lazy_cols, lazy_format, LazyCols};
+
+    const COLUMNS: &[&str] = &["id", "name", "email"];
+
+    #[test]
+    fn const_columns() {
+        const HEADER: LazyCols = lazy_cols!(COLUMNS, " | ");
+
+        assert_eq!(HEADER.to_string(), "id | name | email");
+        assert_eq!(HEADER.columns(), COLUMNS);
+        assert_eq!(
+            lazy_format!("[{HEADER}]\n[{}]", lazy_cols!(&COLUMNS[1..], ",")).to_string(),
+            "[id | name | email]\n[name,email]"
+        );
+    }
+
+    #[test]
+    fn short_slices() {
+        const EMPTY: &[&str] = &[];
+
+        assert_eq!(lazy_cols!(EMPTY, " | ").to_string(), "");
+        assert_eq!(lazy_cols!(&COLUMNS[..1], " | ").to_string(), "id");
+        assert_eq!(lazy_cols!(&["", ""], "-").to_string(), "-");
+    }
+}
+
+mod builder {
+    use std::cell::Cell;
+
+    use lazy_format::{lazy_format, LazyBuilder};
+
+    #[test]
+    fn data_driven() {
+        let columns = ["a", "b", "c"];
+        let values = [1, 22, 333];
+
+        let builder = columns
+            .iter()
+            .zip(values)
+            .enumerate()
+            .fold(LazyBuilder::new(), |builder, (i, (column, value))| {
+                let builder = if i > 0 { builder.lit("&") } else { builder };
+                builder.arg(column).lit("=").arg(value)
+            });
+        let built = builder.build();
+
+        assert_eq!(built.to_string(), "a=1&b=22&c=333");
+        assert_eq!(LazyBuilder::new().build().to_string(), "");
+    }
+
+    #[test]
+    fn args_are_lazy() {
+        let counter = Cell::new(0);
+        let counter = &counter;
+        let next = lazy_format::make_lazy_format!(|f| {
+            counter.set(counter.get() + 1);
+            write!(f, "{}", counter.get())
+        });
+
+        let built = LazyBuilder::new()
+            .lit("[")
+            .arg(next)
+            .lit("|")
+            .arg(lazy_format!("{:>3}", "x"))
+            .lit("]")
+            .build();
+
+        assert_eq!(counter.get(), 0);
+        assert_eq!(built.to_string(), "[1|  x]");
+        assert_eq!(built.to_string(), "[2|  x]");
+
+        // Arguments are formatted as with `{}`, ignoring the outer formatter
+        assert_eq!(format!("{:>10}", built), "[3|  x]");
+    }
+}
+
+mod size {
+    use std::mem::{align_of_val, size_of, size_of_val};
+
+    use lazy_format::{lazy_format, make_lazy_format};
+
+    #[test]
+    fn no_captures() {
+        assert_eq!(size_of_val(&make_lazy_format!(|f| f.write_str("hello"))), 0);
+
+        // A literal with no arguments is a `LazyStr`
+        assert_eq!(size_of_val(&lazy_format!("hello")), size_of::<&str>());
+    }
+
+    #[test]
+    fn same_as_closure() {
+        let (a, b, c) = (1u8, 2u64, 3u16);
+        let lazy = make_lazy_format!(move |f| write!(f, "{a}{b}{c}"));
+        let closure = move |f: &mut std::fmt::Formatter| write!(f, "{a}{b}{c}");
+
+        assert_eq!(size_of_val(&lazy), size_of_val(&closure));
+        assert_eq!(align_of_val(&lazy), align_of_val(&closure));
+
+        let traits = make_lazy_format!(
+            move |f| write!(f, "{a}{b}{c}"),
+            LowerHex => |f| write!(f, "{a:x}{b:x}{c:x}"),
+        );
+        assert_eq!(size_of_val(&traits), size_of_val(&closure));
+    }
+
+    #[test]
+    fn sum_of_arguments() {
+        let (a, b) = (1i32, 2i32);
+        assert_eq!(size_of_val(&lazy_format!("{a} {b}")), 8);
+
+        let name = "name";
+        let count = 3usize;
+        assert_eq!(
+            size_of_val(&lazy_format!("{name}: {count}")),
+            size_of::<&str>() + size_of::<usize>()
+        );
+
+        let (x, y, z) = (1u8, 2u32, 3u8);
+        assert_eq!(size_of_val(&lazy_format!("{x} {y} {z}")), 8);
+    }
+
+    #[test]
+    fn padding_for_alignment() {
+        let (big, small) = (1u64, 2u8);
+        assert_eq!(size_of_val(&lazy_format!("{big} {small}")), 16);
+    }
+
+    #[test]
+    fn borrowed_arguments() {
+        let names = vec!["a".to_owned(), "b".to_owned()];
+        assert_eq!(size_of_val(&lazy_format!("{:?}", ref names)), size_of::<&Vec<String>>());
+
+        let names = &names;
+        assert_eq!(
+            size_of_val(&lazy_format!("{name}" sep ", " for name in names)),
+            size_of::<&Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn conditionals() {
+        let value = 3u16;
+        let label = "label";
+        let matched = lazy_format!(match (value) {
+            0 => "zero",
+            n => ("{label}: {n}"),
+        });
+        assert_eq!(size_of_val(&matched), size_of::<(&str, u16)>());
+    }
+}
+
+mod number {
+    use std::time::Duration;
+
+    use lazy_format::lazy_format;
+    use lazy_format::number::{fixed_num, group_digits, human_bytes, human_duration, percent};
+
+    #[test]
+    fn group_digits_extremes() {
+        assert_eq!(group_digits(0u32, ',').to_string(), "0");
+        assert_eq!(group_digits(100, ',').to_string(), "100");
+        assert_eq!(group_digits(1000, ',').to_string(), "1,000");
+        assert_eq!(
+            group_digits(u64::MAX, ',').to_string(),
+            "18,446,744,073,709,551,615"
+        );
+        assert_eq!(
+            group_digits(i64::MIN, ',').to_string(),
+            "-9,223,372,036,854,775,808"
+        );
+        assert_eq!(group_digits(-12i8, ',').to_string(), "-12");
+    }
+
+    #[test]
+    fn group_digits_multibyte_separator() {
+        assert_eq!(
+            group_digits(12345678usize, '\u{202f}').to_string(),
+            "12\u{202f}345\u{202f}678"
+        );
+    }
+
+    #[test]
+    fn group_digits_flags() {
+        assert_eq!(format!("{:+}", group_digits(1234, ',')), "+1,234");
+        assert_eq!(format!("{:<8}|", group_digits(-1234, ',')), "-1,234  |");
+        assert_eq!(format!("{:*^9}", group_digits(1234, ',')), "**1,234**");
+    }
+
+    #[test]
+    fn human_bytes_boundaries() {
+        assert_eq!(human_bytes(0).to_string(), "0 B");
+        assert_eq!(human_bytes(1023).to_string(), "1023 B");
+        assert_eq!(human_bytes(1024).to_string(), "1.0 KiB");
+        assert_eq!(human_bytes(1024 * 1024 - 1).to_string(), "1.0 MiB");
+        assert_eq!(human_bytes(1024 * 1024 - 60).to_string(), "1023.9 KiB");
+        assert_eq!(human_bytes(5 << 30).to_string(), "5.0 GiB");
+        assert_eq!(human_bytes(u64::MAX).to_string(), "16.0 EiB");
+        assert_eq!(format!("{:*^10}", human_bytes(1536)), "*1.5 KiB**");
+    }
+
+    #[test]
+    fn human_duration_boundaries() {
+        let secs = Duration::from_secs;
+
+        assert_eq!(human_duration(Duration::ZERO).to_string(), "0s");
+        assert_eq!(human_duration(Duration::from_nanos(999)).to_string(), "999ns");
+        assert_eq!(human_duration(Duration::from_nanos(1000)).to_string(), "1µs");
+        assert_eq!(human_duration(Duration::from_micros(1500)).to_string(), "1ms");
+        assert_eq!(human_duration(Duration::from_millis(999)).to_string(), "999ms");
+        assert_eq!(human_duration(secs(1)).to_string(), "1s");
+        assert_eq!(human_duration(secs(59)).to_string(), "59s");
+        assert_eq!(human_duration(secs(60)).to_string(), "1m 0s");
+        assert_eq!(human_duration(secs(3599)).to_string(), "59m 59s");
+        assert_eq!(human_duration(secs(3600)).to_string(), "1h 0m");
+        assert_eq!(human_duration(secs(86399)).to_string(), "23h 59m");
+        assert_eq!(human_duration(secs(86400 + 3 * 3600)).to_string(), "1d 3h");
+        assert_eq!(human_duration(Duration::MAX).to_string(), "213503982334601d 7h");
+        assert_eq!(format!("{:>6}|", human_duration(secs(5))), "    5s|");
+    }
+
+    #[test]
+    fn fixed_num_width_boundary() {
+        assert_eq!(fixed_num(9999, 4, '#').to_string(), "9999");
+        assert_eq!(fixed_num(10000, 4, '#').to_string(), "####");
+        assert_eq!(fixed_num(-999, 4, '#').to_string(), "-999");
+        assert_eq!(fixed_num(-1000, 4, '#').to_string(), "####");
+        assert_eq!(fixed_num(0, 0, '#').to_string(), "");
+        assert_eq!(fixed_num(1.5, 2, '\u{2026}').to_string(), "\u{2026}\u{2026}");
+    }
+
+    #[test]
+    fn fixed_num_alignment() {
+        assert_eq!(format!("{:^6}", fixed_num(12, 5, '#')), " 12  ");
+        assert_eq!(format!("{:*<5}", fixed_num(12, 4, '#')), "12**");
+        assert_eq!(format!("{:<3}", fixed_num(1234, 3, '#')), "###");
+    }
+
+    #[test]
+    fn fixed_num_long_values() {
+        // f64::MAX is far longer than the stack buffer
+        assert_eq!(fixed_num(f64::MAX, 100, '#').to_string(), "#".repeat(100));
+
+        let cells = [1, 22, 333_333];
+        let row = lazy_format!(("{}", fixed_num(cell, 4, '#')) sep "|" for cell in cells);
+        assert_eq!(row.to_string(), "   1|  22|####");
+    }
+
+    #[test]
+    fn percent_zero_total() {
+        assert_eq!(percent(0, 0, 0).to_string(), "--%");
+        assert_eq!(percent(5, 0, 2).to_string(), "--%");
+        assert_eq!(format!("{:>5}", percent(0, 0, 2)), "  --%");
+    }
+
+    #[test]
+    fn percent_exact() {
+        assert_eq!(percent(0, 10, 0).to_string(), "0%");
+        assert_eq!(percent(0, 10, 2).to_string(), "0.00%");
+        assert_eq!(percent(1, 4, 0).to_string(), "25%");
+        assert_eq!(percent(1, 4, 2).to_string(), "25.00%");
+        assert_eq!(percent(10, 10, 1).to_string(), "100.0%");
+        assert_eq!(percent(3, 2, 0).to_string(), "150%");
+        assert_eq!(percent(1, 1_000_000, 4).to_string(), "0.0001%");
+        assert_eq!(
+            percent(u64::MAX, 1, 9).to_string(),
+            "1844674407370955161500.000000000%"
+        );
+    }
+
+    #[test]
+    fn percent_rounding() {
+        assert_eq!(percent(1, 3, 0).to_string(), "33%");
+        assert_eq!(percent(2, 3, 0).to_string(), "67%");
+        assert_eq!(percent(2, 3, 2).to_string(), "66.67%");
+        assert_eq!(percent(1, 200, 0).to_string(), "1%");
+        assert_eq!(percent(1, 201, 0).to_string(), "0%");
+        assert_eq!(percent(999, 1000, 0).to_string(), "100%");
+        assert_eq!(percent(1, 3, 20).to_string(), "33.333333333%");
+        assert_eq!(percent(u64::MAX - 1, u64::MAX, 2).to_string(), "100.00%");
+    }
+}
+
+mod runtime {
+    use std::fmt::{Display, Write};
+
+    use lazy_format::runtime::{lazy_runtime, lazy_runtime_named};
+
+    #[test]
+    fn positional_and_indexed() {
+        let result = lazy_runtime("{} {} {0} {{{}}}", &[&1, &"two", &3.5]);
+        assert_eq!(result.to_string(), "1 two 1 {3.5}");
+    }
+
+    #[test]
+    fn named_args() {
+        let name = String::from("World");
+        let named: [(&str, &dyn Display); 2] = [("greeting", &"Hello"), ("name", &name)];
+        let result = lazy_runtime_named("{greeting}, {name}! ({})", &[&10], &named);
+        assert_eq!(result.to_string(), "Hello, World! (10)");
+    }
+
+    #[test]
+    fn malformed_templates() {
+        for template in ["{", "}", "{} {}", "{5}", "{missing}", "abc { def"] {
+            let mut dest = String::new();
+            let result = write!(&mut dest, "{}", lazy_runtime(template, &[&1]));
+            assert!(result.is_err(), "template {template:?} should fail");
+        }
+    }
+}
+
+mod ext {
+    use std::fmt;
+
+    use lazy_format::prelude::*;
+
+    #[test]
+    fn write_tee() {
+        let mut first = String::new();
+        let mut second = String::new();
+
+        let items = [1, 2, 3];
+        lazy_format!("{item}, " for item in items)
+            .write_tee(&mut [&mut first, &mut second])
+            .unwrap();
+
+        assert_eq!(first, "1, 2, 3, ");
+        assert_eq!(second, "1, 2, 3, ");
+    }
+
+    #[test]
+    fn write_tee_stops_on_error() {
+        struct BadDest;
+
+        impl fmt::Write for BadDest {
+            fn write_str(&mut self, _s: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        let mut before = String::new();
+        let mut after = String::new();
+
+        lazy_format!("{item}" for item in [1, 2])
+            .write_tee(&mut [&mut before, &mut BadDest, &mut after])
+            .unwrap_err();
+
+        assert_eq!(before, "1");
+        assert_eq!(after, "");
+    }
+
+    #[test]
+    fn eq_str_chunks() {
+        let parts = ["ab", "", "cde", "f"];
+        let result = lazy_format!("{part}" for part in parts);
+
+        assert!(result.eq_str("abcdef"));
+        assert!(!result.eq_str("abcde"));
+        assert!(!result.eq_str("abcdefg"));
+        assert!(!result.eq_str("abXdef"));
+        assert!(!result.eq_str(""));
+        assert!(lazy_format!("{part}" for part in [0; 0]).eq_str(""));
+    }
+
+    #[test]
+    fn eq_str_stops_early() {
+        let emitter = &crate::ValueEmitter::new();
+        let result = lazy_format!(("{} ", emitter.next()) repeat 10);
+
+        assert!(!result.eq_str("1 2 X"));
+        assert_eq!(emitter.count(), 3);
+    }
+
+    #[test]
+    fn render_to_slice_exact() {
+        let mut buffer = [0u8; 6];
+        let len = lazy_format!("{}-{}", "abc", 12).render_to_slice(&mut buffer);
+        assert_eq!(len, Ok(6));
+        assert_eq!(&buffer, b"abc-12");
+
+        let len = lazy_format!("").render_to_slice(&mut []);
+        assert_eq!(len, Ok(0));
+    }
+
+    #[test]
+    fn render_to_slice_full() {
+        use lazy_format::{BufferFull, RenderError};
+
+        let mut buffer = [b'_'; 7];
+        let result = lazy_format!("ab{}", "αβγ").render_to_slice(&mut buffer);
+        assert_eq!(result, Err(RenderError::Full(BufferFull)));
+
+        // "ab", "α", and "β" fit, but "γ" would be split, so it's left out
+        assert_eq!(&buffer, "abαβ_".as_bytes());
+
+        let mut buffer = [b'_'; 3];
+        let result = lazy_format!("ab{}", "αβγ").render_to_slice(&mut buffer);
+        assert_eq!(result, Err(RenderError::Full(BufferFull)));
+        assert_eq!(&buffer, b"ab_");
+    }
+
+    #[test]
+    fn render_to_slice_error() {
+        use lazy_format::{fallible, RenderError};
+
+        let failing = fallible(|_f| Err("failed"));
+
+        let mut buffer = [0u8; 10];
+        assert_eq!(failing.render_to_slice(&mut buffer), Err(RenderError::Format));
+        assert_eq!(failing.take_error(), Some("failed"));
+    }
+
+    #[test]
+    fn render_to_heapless_exact() {
+        let rendered = lazy_format!("{}-{}", "abc", 12).render_to_heapless::<6>();
+        assert_eq!(rendered.as_deref(), Ok("abc-12"));
+
+        let rendered = lazy_format!("{}", "日本").render_to_heapless::<6>();
+        assert_eq!(rendered.as_deref(), Ok("日本"));
+
+        let rendered = lazy_format!("").render_to_heapless::<0>();
+        assert_eq!(rendered.as_deref(), Ok(""));
+    }
+
+    #[test]
+    fn render_to_heapless_overflow() {
+        use lazy_format::{CapacityError, RenderError};
+
+        let items = [1, 2, 3];
+        let list = lazy_format!("{item}" sep ", " for item in items);
+
+        assert_eq!(list.render_to_heapless::<7>().as_deref(), Ok("1, 2, 3"));
+        assert_eq!(list.render_to_heapless::<6>(), Err(RenderError::Full(CapacityError)));
+        assert_eq!(list.render_to_heapless::<0>(), Err(RenderError::Full(CapacityError)));
+
+        // "本" would be split at a capacity of 5
+        assert_eq!(lazy_format!("{}", "日本").render_to_heapless::<5>(), Err(RenderError::Full(CapacityError)));
+    }
+
+    #[test]
+    fn render_to_heapless_error() {
+        use lazy_format::{fallible, RenderError};
+
+        let failing = fallible(|_f| Err("failed"));
+        assert_eq!(failing.render_to_heapless::<10>(), Err(RenderError::Format));
+        assert_eq!(failing.take_error(), Some("failed"));
+    }
+
+    #[test]
+    fn render_to_byte_limit() {
+        use lazy_format::{RenderError, TooLong};
+
+        let parts = ["ab", "日本", "cd"];
+        let joined = lazy_format!("{part}" for part in parts);
+        let too_long = |max| -> TooLong {
+            match joined.render_to_byte_limit(max) {
+                Err(RenderError::Full(err)) => err,
+                result => panic!("expected the output to be cut off, got {:?}", result),
+            }
+        };
+
+        assert_eq!(joined.render_to_byte_limit(10).unwrap(), "ab日本cd");
+        assert_eq!(joined.render_to_byte_limit(100).unwrap(), "ab日本cd");
+
+        // Each of "日本" is 3 bytes
+        assert_eq!(too_long(9).partial(), "ab日本c");
+        assert_eq!(too_long(7).partial(), "ab日");
+        assert_eq!(too_long(6).partial(), "ab日");
+        assert_eq!(too_long(4).into_partial(), "ab");
+        assert_eq!(too_long(0).partial(), "");
+        assert_eq!(lazy_format!("").render_to_byte_limit(0).unwrap(), "");
+    }
+
+    #[test]
+    fn render_to_byte_limit_error() {
+        use lazy_format::{fallible, RenderError};
+
+        let failing = fallible(|_f| Err("failed"));
+        assert_eq!(failing.render_to_byte_limit(10), Err(RenderError::Format));
+        assert_eq!(failing.take_error(), Some("failed"));
+    }
+
+    #[test]
+    fn to_string_sized() {
+        let big = "x".repeat(1000);
+        let result = lazy_format!(("{}{}", big, i) for i in 0..10)
+            .to_string_sized()
+            .unwrap();
+
+        assert_eq!(result.len(), 10010);
+        assert_eq!(result.capacity(), 10010);
+        assert!(result.ends_with("x9"));
+
+        assert_eq!(lazy_format!("").to_string_sized().unwrap(), "");
+    }
+
+    #[test]
+    fn to_string_sized_error() {
+        use lazy_format::fallible;
+
+        let failing = fallible(|_f| Err("failed"));
+        assert_eq!(failing.to_string_sized(), Err(fmt::Error));
+        assert_eq!(failing.take_error(), Some("failed"));
+    }
+
+    #[test]
+    fn char_len() {
+        let parts = ["ab", "", "αβγ", "🦀"];
+        assert_eq!(lazy_format!("{part}" for part in parts).char_len(), Ok(6));
+        assert_eq!(lazy_format!("{}", 'é').char_len(), Ok(1));
+        assert_eq!(lazy_format!("").char_len(), Ok(0));
+
+        let result = lazy_format!("{v} " for v in try [Ok(1), Err(())]);
+        assert_eq!(result.char_len(), Err(fmt::Error));
+    }
+}
+
+mod io {
+    use std::fmt::{self, Write};
+    use std::io;
+
+    use lazy_format::io::IoWriter;
+    use lazy_format::prelude::*;
+    use lazy_format::write_lazy;
+
+    /// A writer that accepts a limited number of bytes, and then fails
+    struct Limited {
+        written: Vec<u8>,
+        limit: usize,
+    }
+
+    impl io::Write for Limited {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let available = self.limit - self.written.len();
+            if available == 0 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "limit reached"));
+            }
+
+            let len = buf.len().min(available);
+            self.written.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn io_error_is_kept() {
+        let mut dest = Limited {
+            written: Vec::new(),
+            limit: 5,
+        };
+        let items = ["abc", "def", "ghi"];
+        let list = lazy_format!("{item}" for item in items);
+
+        let mut writer = IoWriter::new(&mut dest);
+        assert!(write!(writer, "{}", list).is_err());
+        assert_eq!(writer.take_error().unwrap().kind(), io::ErrorKind::WriteZero);
+        assert!(writer.take_error().is_none());
+
+        assert_eq!(dest.written, b"abcde");
+
+        dest.written.clear();
+        let err = list.render_to_writer(&mut dest).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn formatter_error_is_other() {
+        let failing = make_lazy_format!(|f| {
+            f.write_str("partial")?;
+            Err(fmt::Error)
+        });
+
+        let mut out: Vec<u8> = Vec::new();
+        let err = failing.render_to_writer(&mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert_eq!(out, b"partial");
+    }
+
+    #[test]
+    fn write_lazy_to_vec() {
+        let mut out: Vec<u8> = Vec::new();
+        let values = [1.5, 2.25];
+
+        write_lazy(&mut out, &lazy_format!("{v:.1}" sep ", " for v in values)).unwrap();
+        write_lazy(&mut out, "; done").unwrap();
+        let dynamic: &dyn std::fmt::Display = &'!';
+        write_lazy(&mut out, dynamic).unwrap();
+
+        assert_eq!(out, "1.5, 2.2; done!".as_bytes());
+    }
+
+    #[test]
+    fn write_lazy_to_failing_writer() {
+        let mut dest = Limited {
+            written: Vec::new(),
+            limit: 4,
+        };
+
+        let err = write_lazy(&mut dest, &lazy_format!("{}-{}", "ab", "cd")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+        assert_eq!(dest.written, b"ab-c");
+    }
+}
+
+mod transform {
+    use std::fmt::Write;
+
+    use crate::ValueEmitter;
+    use lazy_format::transform::{
+        column, dedent, escape_csv, escape_html, escape_json_str, indent, opt_display, padded,
+        prefix_lines, render_once, single_line, to_lower, to_upper, truncate, truncate_cols,
+        with_flags, Align, Flags,
+    };
+    use lazy_format::{lazy_format, make_lazy_format};
+
+    #[test]
+    fn single_line_line_endings() {
+        assert_eq!(single_line("no breaks", " | ").to_string(), "no breaks");
+        assert_eq!(single_line("a\nb\r\nc", " | ").to_string(), "a | b | c");
+        assert_eq!(single_line("\n\r\n", "/").to_string(), "//");
+        assert_eq!(single_line("lone\rcr\r", "/").to_string(), "lone\rcr\r");
+        assert_eq!(single_line("a\r\r\nb", "/").to_string(), "a\r/b");
+    }
+
+    #[test]
+    fn single_line_across_chunks() {
+        let split_crlf = make_lazy_format!(|f| {
+            f.write_str("one\r")?;
+            f.write_str("\ntwo\r")?;
+            f.write_str("\r")?;
+            f.write_str("")?;
+            f.write_str("\nthree\r")
+        });
+        assert_eq!(single_line(split_crlf, " | ").to_string(), "one | two\r | three\r");
+
+        let lines = ["x\r", "\ny", "\r"];
+        let joined = lazy_format!("{line}" for line in lines);
+        assert_eq!(single_line(joined, ", ").to_string(), "x, y\r");
+    }
+
+    #[test]
+    fn escape_csv_fields() {
+        assert_eq!(escape_csv("plain text").to_string(), "plain text");
+        assert_eq!(escape_csv("").to_string(), "");
+        assert_eq!(escape_csv("a,b").to_string(), r#""a,b""#);
+        assert_eq!(escape_csv(r#"6" tall"#).to_string(), r#""6"" tall""#);
+        assert_eq!(escape_csv(r#""""#).to_string(), r#""""""""#);
+        assert_eq!(escape_csv("line\nbreak").to_string(), "\"line\nbreak\"");
+        assert_eq!(escape_csv("crlf\r\n").to_string(), "\"crlf\r\n\"");
+    }
+
+    #[test]
+    fn escape_csv_across_chunks() {
+        let items = ["a", "b", "c\"d"];
+        let plain = lazy_format!("{item}" sep " " for item in &items[..2]);
+        let quoted = lazy_format!("{item}" sep "," for item in items);
+
+        assert_eq!(escape_csv(plain).to_string(), "a b");
+        assert_eq!(escape_csv(quoted).to_string(), r#""a,b,c""d""#);
+
+        let people = [("Ann", "Lee"), ("Bo", "\"B\"")];
+        let row = lazy_format!(
+            ("{}", escape_csv(lazy_format!("{} {}", first, last))) sep "," for (first, last) in people
+        );
+        assert_eq!(row.to_string(), r#"Ann Lee,"Bo ""B""""#);
+    }
+
+    #[test]
+    fn opt_display_some_and_none() {
+        let emitter = ValueEmitter::new();
+        let emitter = &emitter;
+        let value = opt_display(Some(lazy_format!("<{}>", emitter.next())), "none");
+        assert_eq!(emitter.count(), 0);
+        assert_eq!(value.to_string(), "<1>");
+        assert_eq!(value.to_string(), "<2>");
+
+        let fallback = lazy_format!("none ({})", emitter.next());
+        let missing = opt_display(None::<&str>, fallback);
+        assert_eq!(missing.to_string(), "none (3)");
+
+        let nested = opt_display(Some(opt_display(None::<i32>, "inner")), "outer");
+        assert_eq!(nested.to_string(), "inner");
+        assert_eq!(format!("{:>6}", opt_display(Some(42), "-")), "    42");
+    }
+
+    #[test]
+    fn render_once_runs_inner_once() {
+        let emitter = ValueEmitter::new();
+        let emitter = &emitter;
+        let once = render_once(lazy_format!("value {}", emitter.next()));
+
+        assert_eq!(emitter.count(), 0);
+        assert_eq!(once.to_string(), "value 1");
+        assert_eq!(once.to_string(), "value 1");
+        assert_eq!(format!("{:-^11.7}", once), "--value 1--");
+        assert_eq!(emitter.count(), 1);
+    }
+
+    #[test]
+    fn render_once_retries_after_error() {
+        let emitter = ValueEmitter::new();
+        let emitter = &emitter;
+        let once = render_once(make_lazy_format!(|f| {
+            if emitter.next() == "1" {
+                Err(std::fmt::Error)
+            } else {
+                f.write_str("ok")
+            }
+        }));
+
+        let mut dest = String::new();
+        assert!(write!(dest, "{}", once).is_err());
+        assert_eq!(once.to_string(), "ok");
+        assert_eq!(once.to_string(), "ok");
+        assert_eq!(emitter.count(), 2);
+    }
+
+    #[test]
+    fn dedent_common_indentation() {
+        assert_eq!(dedent("    a\n      b\n    c").to_string(), "a\n  b\nc");
+        assert_eq!(dedent("  a\nb\n").to_string(), "  a\nb\n");
+        assert_eq!(dedent("\t\ta\n\tb\n").to_string(), "\ta\nb\n");
+        assert_eq!(dedent("").to_string(), "");
+        assert_eq!(dedent("  only").to_string(), "only");
+    }
+
+    #[test]
+    fn dedent_mixed_indentation() {
+        // Tabs and spaces only match themselves
+        assert_eq!(dedent("\ta\n    b\n").to_string(), "\ta\n    b\n");
+        assert_eq!(dedent("  \ta\n  \t\tb\n    c\n").to_string(), "\ta\n\t\tb\n  c\n");
+        assert_eq!(dedent("\t  a\n\t    b\n").to_string(), "a\n  b\n");
+    }
+
+    #[test]
+    fn dedent_blank_lines() {
+        assert_eq!(dedent("    a\n\n    b\n").to_string(), "a\n\nb\n");
+        assert_eq!(dedent("    a\n  \n\t\n    b").to_string(), "a\n\n\nb");
+        assert_eq!(dedent("   \n  \n").to_string(), "\n\n");
+        assert_eq!(dedent("    a\r\n      b\r\n   \r\n").to_string(), "a\r\n  b\r\n\r\n");
+    }
+
+    #[test]
+    fn dedent_streamed_output() {
+        let items = ["one", "two"];
+        let list = lazy_format!("        - {item}\n" for item in items);
+        let nested = lazy_format!("items:\n{}", indent(2, dedent(list)));
+
+        assert_eq!(nested.to_string(), "items:\n  - one\n  - two\n");
+    }
+
+    #[test]
+    fn dedent_propagates_error() {
+        let failing = make_lazy_format!(|f| {
+            f.write_str("    abc\n")?;
+            Err(std::fmt::Error)
+        });
+
+        let mut dest = String::new();
+        assert!(write!(dest, "{}", dedent(failing)).is_err());
+        assert_eq!(dest, "");
+    }
+
+    #[test]
+    fn with_flags_combinations() {
+        let flags = |sign_plus, alternate, zero_pad| Flags {
+            sign_plus,
+            alternate,
+            zero_pad,
+            width: Some(8),
+            precision: None,
+        };
+
+        assert_eq!(with_flags(42, flags(false, false, false)).to_string(), "      42");
+        assert_eq!(with_flags(42, flags(true, false, true)).to_string(), "+0000042");
+        assert_eq!(with_flags(42, flags(true, true, true)).to_string(), "+0000042");
+        assert_eq!(with_flags("ab", flags(false, true, false)).to_string(), "ab      ");
+
+        let precise = Flags {
+            precision: Some(2),
+            ..Flags::default()
+        };
+        assert_eq!(with_flags(1.23456, precise).to_string(), "1.23");
+    }
+
+    #[test]
+    fn with_flags_ignores_outer_flags() {
+        let inspect = make_lazy_format!(|f| write!(
+            f,
+            "{}/{}/{:?}/{:?}",
+            f.alternate(),
+            f.sign_plus(),
+            f.width(),
+            f.precision()
+        ));
+
+        assert_eq!(
+            format!("{:+#9.3}", with_flags(&inspect, Flags::default())),
+            "false/false/None/None"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                with_flags(
+                    &inspect,
+                    Flags {
+                        alternate: true,
+                        sign_plus: true,
+                        zero_pad: false,
+                        width: Some(4),
+                        precision: Some(1),
+                    }
+                )
+            ),
+            "true/true/Some(4)/Some(1)"
+        );
+
+        // Basic lazy formats are truncated to the precision
+        let short = Flags {
+            precision: Some(3),
+            ..Flags::default()
+        };
+        let truncated = with_flags(lazy_format!("{}", "abcdef"), short);
+        assert_eq!(format!("{:.5}", truncated), "abc");
+    }
+
+    #[test]
+    fn indent_nested() {
+        let inner = lazy_format!("one\ntwo\n\nthree");
+        let middle = lazy_format!("begin\n{}\nend", indent(2, inner));
+        let outer = indent(2, middle);
+
+        assert_eq!(
+            outer.to_string(),
+            "  begin\n    one\n    two\n\n    three\n  end"
+        );
+    }
+
+    #[test]
+    fn indent_across_chunks() {
+        let parts = ["a", "b\nc", "", "d\n", "e"];
+        let result = indent(3, lazy_format!("{part}" for part in parts));
+        assert_eq!(result.to_string(), "   ab\n   cd\n   e");
+    }
+
+    #[test]
+    fn indent_wide() {
+        let result = indent(40, "x");
+        assert_eq!(result.to_string(), format!("{}x", " ".repeat(40)));
+    }
+
+    #[test]
+    fn prefix_lines_across_chunks() {
+        let parts = ["a", "b\nc", "", "\n", "\n", "d\n"];
+        let result = prefix_lines(lazy_format!("{part}" for part in parts), "| ");
+        assert_eq!(result.to_string(), "| ab\n| c\n| \n| d\n");
+    }
+
+    #[test]
+    fn prefix_lines_nested() {
+        let inner = prefix_lines("one\ntwo", "> ");
+        let outer = prefix_lines(lazy_format!("{inner}\nthree\n"), "> ");
+
+        assert_eq!(outer.to_string(), "> > one\n> > two\n> three\n");
+        assert_eq!(prefix_lines("", "> ").to_string(), "");
+        assert_eq!(prefix_lines("\n", "> ").to_string(), "> \n");
+    }
+
+    #[test]
+    fn truncate_multibyte() {
+        let parts = ["αβ", "γδε", "ζ"];
+        // The ellipsis counts against the limit
+        let result = truncate(lazy_format!("{part}" for part in parts), 5, "...");
+        assert_eq!(result.to_string(), "αβ...");
+
+        // Fits exactly, so there's no ellipsis
+        let result = truncate(lazy_format!("{part}" for part in parts), 6, "...");
+        assert_eq!(result.to_string(), "αβγδεζ");
+
+        // The ellipsis itself is cut off if it doesn't fit
+        let result = truncate(lazy_format!("{part}" for part in parts), 2, "...");
+        assert_eq!(result.to_string(), "..");
+
+        let result = truncate(lazy_format!("{part}" for part in parts), 0, "…");
+        assert_eq!(result.to_string(), "");
+    }
+
+    #[test]
+    fn truncate_stops_early() {
+        let emitter = &ValueEmitter::new();
+        let result = truncate(lazy_format!(("{} ", emitter.next()) repeat 10), 5, "~");
+
+        assert_eq!(result.to_string(), "1 2 ~");
+        assert_eq!(emitter.count(), 3);
+    }
+
+    #[test]
+    fn truncate_long_ellipsis() {
+        let ellipsis = "…".repeat(30);
+        let text = &"x".repeat(40);
+
+        // The 30 chars after the first 10 are held back, which is more than
+        // the internal buffer
+        let result = truncate(lazy_format!("{text}"), 40, &ellipsis);
+        assert_eq!(result.to_string(), *text);
+
+        let result = truncate(lazy_format!("{text}!"), 40, &ellipsis);
+        assert_eq!(result.to_string(), format!("{}{}", &text[..10], ellipsis));
+    }
+
+    #[test]
+    fn escape_html_nested() {
+        let name = "Tom & \"Jerry\"";
+        let inner = lazy_format!("<em>{name}</em>");
+        let outer = lazy_format!("<p>{}</p>", escape_html(lazy_format!("{inner}'s")));
+
+        assert_eq!(
+            outer.to_string(),
+            "<p>&lt;em&gt;Tom &amp; &quot;Jerry&quot;&lt;/em&gt;&#39;s</p>"
+        );
+    }
+
+    #[test]
+    fn escape_html_across_chunks() {
+        let parts = ["a<", "", ">b", "&&", "plain"];
+        let result = escape_html(lazy_format!("{part}" for part in parts));
+        assert_eq!(result.to_string(), "a&lt;&gt;b&amp;&amp;plain");
+    }
+
+    #[test]
+    fn escape_json_str_control_chars() {
+        let value = "tab\there\r\n\u{8}\u{c}\u{0}\u{1f} é";
+        assert_eq!(
+            escape_json_str(value).to_string(),
+            r"tab\there\r\n\b\f\u0000\u001f é"
+        );
+    }
+
+    #[test]
+    fn escape_json_str_nested() {
+        let path = r"C:\dir";
+        let inner = lazy_format!("\"{path}\"");
+        let parts = ["a\"", "", "\\b"];
+        let result = escape_json_str(lazy_format!("{inner} {}", lazy_format!("{part}" for part in parts)));
+
+        assert_eq!(result.to_string(), r#"\"C:\\dir\" a\"\\b"#);
+    }
+
+    #[test]
+    fn padded_complex() {
+        let emitter = &ValueEmitter::new();
+        let flag = true;
+        let result = padded(lazy_format!(
+            if flag => ("<{}>", lazy_format!(("{} ", emitter.next()) repeat 2))
+            else => "none"
+        ));
+
+        assert_eq!(format!("{:>10}|", result), "    <1 2 >|");
+        assert_eq!(format!("{:-^9}", result), "-<3 4 >--");
+        assert_eq!(format!("{}", result), "<5 6 >");
+        assert_eq!(emitter.count(), 6);
+    }
+
+    #[test]
+    fn padded_propagates_error() {
+        let failing = make_lazy_format!(|f| {
+            f.write_str("abc")?;
+            Err(std::fmt::Error)
+        });
+
+        let mut dest = String::new();
+        assert!(write!(dest, "{:>10}", padded(failing)).is_err());
+        assert_eq!(dest, "");
+    }
+
+    #[test]
+    fn truncate_cols_wide() {
+        let parts = ["ab", "日本", "c"];
+
+        // Fits exactly, so there's no ellipsis
+        let result = truncate_cols(lazy_format!("{part}" for part in parts), 7, "…");
+        assert_eq!(result.to_string(), "ab日本c");
+
+        // One column short: the ellipsis takes the place of "本c"
+        let result = truncate_cols(lazy_format!("{part}" for part in parts), 6, "…");
+        assert_eq!(result.to_string(), "ab日…");
+
+        // A wide char is never split
+        let result = truncate_cols(lazy_format!("{part}" for part in parts), 4, "…");
+        assert_eq!(result.to_string(), "ab…");
+
+        let result = truncate_cols(lazy_format!("{part}" for part in parts), 6, "...");
+        assert_eq!(result.to_string(), "ab...");
+
+        let result = truncate_cols(lazy_format!("{part}" for part in parts), 0, "");
+        assert_eq!(result.to_string(), "");
+    }
+
+    #[test]
+    fn truncate_cols_stops_early() {
+        let emitter = &ValueEmitter::new();
+        let result = truncate_cols(lazy_format!(("{} ", emitter.next()) repeat 10), 5, "~");
+
+        assert_eq!(result.to_string(), "1 2 ~");
+        assert_eq!(emitter.count(), 3);
+    }
+
+    #[test]
+    fn truncate_cols_long_ellipsis() {
+        let ellipsis = ".".repeat(90);
+
+        // The 90 columns after the first 10 are held back, which is more
+        // than the internal buffer
+        let text = &"x".repeat(100);
+        let result = truncate_cols(lazy_format!("{text}"), 100, &ellipsis);
+        assert_eq!(result.to_string(), *text);
+
+        let text = &"x".repeat(101);
+        let result = truncate_cols(lazy_format!("{text}"), 100, &ellipsis);
+        assert_eq!(result.to_string(), format!("{}{}", &text[..10], ellipsis));
+    }
+
+    #[test]
+    fn truncate_cols_zero_width() {
+        let text = &format!("abc{}", "\u{200B}".repeat(40));
+
+        // The zero-width spaces after "c" are held back with it
+        let result = truncate_cols(lazy_format!("{text}"), 3, "…");
+        assert_eq!(result.to_string(), *text);
+
+        let result = truncate_cols(lazy_format!("{text}d"), 3, "…");
+        assert_eq!(result.to_string(), "ab…");
+    }
+
+    #[test]
+    fn change_case_non_ascii() {
+        let text = "Straße, Ärger, ǅ";
+        assert_eq!(to_upper(text).to_string(), "STRAßE, ÄRGER, ǅ");
+        assert_eq!(to_lower(text).to_string(), "straße, Ärger, ǅ");
+    }
+
+    #[test]
+    fn change_case_long_chunks() {
+        // Longer than the internal buffer, with multibyte chars straddling
+        // its boundaries
+        let text = "aé".repeat(50);
+        let expected = "Aé".repeat(50);
+        assert_eq!(to_upper(&text).to_string(), expected);
+        assert_eq!(to_lower(to_upper(&text)).to_string(), text);
+    }
+
+    #[test]
+    fn column_alignment() {
+        let parts = ["日本", "x"];
+        let inner = lazy_format!("{part}" for part in parts);
+
+        assert_eq!(column(inner, 5, Align::Left).to_string(), "日本x  ");
+        assert_eq!(column(inner, 5, Align::Right).to_string(), "  日本x");
+        assert_eq!(column(inner, 6, Align::Center).to_string(), " 日本x  ");
+        assert_eq!(column(inner, 2, Align::Right).to_string(), "日本x");
+        assert_eq!(column("", 3, Align::Center).to_string(), "   ");
+        assert_eq!(column(inner, 40, Align::Left).to_string().len(), "日本x".len() + 37);
+    }
+
+    #[test]
+    fn column_stops_on_error() {
+        let failing = make_lazy_format!(|f| {
+            f.write_str("abc")?;
+            Err(std::fmt::Error)
+        });
+
+        let mut dest = String::new();
+        assert!(write!(dest, "{}", column(failing, 10, Align::Left)).is_err());
+        assert_eq!(dest, "abc");
+
+        let mut dest = String::new();
+        assert!(write!(dest, "{}", column(failing, 10, Align::Right)).is_err());
+        assert_eq!(dest, "");
+    }
+}
+
+mod serialize {
+    use serde::Serialize;
+
+    use crate::ValueEmitter;
+    use lazy_format::{lazy_cols, lazy_format, make_lazy_format, LazyStr, LazyString};
+
+    #[derive(Serialize)]
+    struct Record<T> {
+        name: &'static str,
+        value: T,
+    }
+
+    #[test]
+    fn lazy_formats_as_strings() {
+        let items = [1, 2, 3];
+        let list = lazy_format!("{item}" sep ", " for item in items);
+        assert_eq!(serde_json::to_string(&list).unwrap(), r#""1, 2, 3""#);
+
+        let hex = make_lazy_format!(
+            |f| write!(f, "{}", 255),
+            UpperHex => |f| write!(f, "{:X}", 255),
+        );
+        assert_eq!(serde_json::to_string(&hex).unwrap(), r#""255""#);
+
+        let quoted = lazy_format!("say \"{}\"", "hi");
+        assert_eq!(serde_json::to_string(&quoted).unwrap(), r#""say \"hi\"""#);
+
+        let record = Record {
+            name: "list",
+            value: list,
+        };
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"name":"list","value":"1, 2, 3"}"#
+        );
+    }
+
+    #[test]
+    fn literals_as_strings() {
+        let literal: LazyStr = lazy_format!("{{plain}}");
+        assert_eq!(serde_json::to_string(&literal).unwrap(), r#""{plain}""#);
+
+        let owned = LazyString::from(String::from("owned"));
+        assert_eq!(serde_json::to_string(&owned).unwrap(), r#""owned""#);
+
+        let cols = lazy_cols!(&["a", "b"], " | ");
+        assert_eq!(serde_json::to_string(&cols).unwrap(), r#""a | b""#);
+    }
+
+    #[test]
+    fn renders_each_time() {
+        let emitter = ValueEmitter::new();
+        let emitter = &emitter;
+        let value = lazy_format!("value {}", emitter.next());
+
+        assert_eq!(serde_json::to_string(&value).unwrap(), r#""value 1""#);
+        assert_eq!(serde_json::to_string(&value).unwrap(), r#""value 2""#);
+    }
+}
+
+mod logging {
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::subscriber::Interest;
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    use lazy_format::{count_renders, lazy_format};
+
+    /// Subscriber that records the fields of every event at or above `level`.
+    struct Recorder {
+        level: Level,
+        fields: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Subscriber for Recorder {
+        fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+            // Other tests may use other subscribers, so don't let `tracing`
+            // cache whether a callsite is enabled
+            Interest::sometimes()
+        }
+
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            *metadata.level() <= self.level
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            struct Visitor<'a>(&'a mut Vec<String>);
+
+            impl Visit for Visitor<'_> {
+                fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                    self.0.push(format!("{}={:?}", field.name(), value));
+                }
+            }
+
+            event.record(&mut Visitor(&mut self.fields.lock().unwrap()));
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn only_formatted_when_recorded() {
+        let fields = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Recorder {
+            level: Level::INFO,
+            fields: Arc::clone(&fields),
+        };
+
+        let items = [1, 2, 3];
+        let summary = count_renders(lazy_format!("{item}" sep ", " for item in items));
+
+        tracing::subscriber::with_default(recorder, || {
+            tracing::debug!(items = %summary);
+            assert_eq!(summary.render_count(), 0);
+
+            tracing::info!(items = %summary);
+            assert_eq!(summary.render_count(), 1);
+        });
+
+        assert_eq!(*fields.lock().unwrap(), ["items=1, 2, 3"]);
     }
 }